@@ -0,0 +1,242 @@
+use std::fmt::{Debug, Display};
+use std::fs::File;
+use std::path::Path;
+use std::str::FromStr;
+
+use anyhow::{anyhow, bail, Context};
+use num_traits::{PrimInt, Signed};
+
+pub const DIAL_SIZE: u8 = 100;
+pub const DIAL_START: u8 = 50;
+
+/// Integer widths the dial can be simulated with (i32, i64, i128, ...)
+pub trait DialInt: PrimInt + Signed + FromStr + Debug + Display {}
+
+impl<T> DialInt for T where T: PrimInt + Signed + FromStr + Debug + Display {}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Direction {
+    Left,
+    Right,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Move<T> {
+    pub direction: Direction,
+    pub distance: T,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Dial<T> {
+    pub position: T,
+    /// number of moves that finished with the dial on 0
+    pub ends_on_zero: T,
+    /// number of clicks that landed on 0, including the ones mid-move
+    pub passes_zero: T,
+}
+
+pub fn parse_move<T: DialInt>(record: &str) -> anyhow::Result<Move<T>> {
+    if record.is_empty() {
+        bail!("Empty move");
+    }
+    let (direction, distance) = record.split_at(1);
+    let direction = match direction {
+        "L" => Direction::Left,
+        "R" => Direction::Right,
+        _ => bail!("Invalid direction {direction:?}"),
+    };
+    let distance = distance
+        .parse::<T>()
+        .map_err(|_| anyhow!("Invalid distance {distance:?}"))?;
+
+    Ok(Move { direction, distance })
+}
+
+pub fn read_moves<T: DialInt>(path: &Path) -> anyhow::Result<Vec<Move<T>>> {
+    let file = File::open(path).with_context(|| format!("Failed to read {}", path.display()))?;
+    let mut reader = csv::ReaderBuilder::new().has_headers(false).from_reader(file);
+
+    reader.records().enumerate().map(|(i, r)| {
+        let record = r?;
+        parse_move(record.as_slice()).with_context(|| format!("Failed to parse move {i}"))
+    }).collect()
+}
+
+impl<T: DialInt> Default for Dial<T> {
+    fn default() -> Self {
+        Dial {
+            position: T::from(DIAL_START).unwrap(),
+            ends_on_zero: T::zero(),
+            passes_zero: T::zero(),
+        }
+    }
+}
+
+impl<T: DialInt> Dial<T> {
+    // returns None if one of the counters overflows
+    fn turn(&mut self, m: &Move<T>) -> Option<()> {
+        let size = T::from(DIAL_SIZE)?;
+
+        // every full rotation goes past 0 exactly once
+        let mut passes = m.distance / size;
+        let rest = m.distance % size;
+
+        let position = match m.direction {
+            Direction::Left => {
+                if self.position != T::zero() && rest >= self.position {
+                    passes = passes.checked_add(&T::one())?;
+                }
+                let position = self.position - rest;
+                if position < T::zero() { position + size } else { position }
+            },
+            Direction::Right => {
+                let position = self.position.checked_add(&rest)?;
+                if position >= size {
+                    passes = passes.checked_add(&T::one())?;
+                    position - size
+                } else {
+                    position
+                }
+            }
+        };
+
+        self.passes_zero = self.passes_zero.checked_add(&passes)?;
+        if position == T::zero() {
+            self.ends_on_zero = self.ends_on_zero.checked_add(&T::one())?;
+        }
+        self.position = position;
+
+        Some(())
+    }
+}
+
+pub fn run_moves<T: DialInt>(moves: &[Move<T>]) -> anyhow::Result<Dial<T>> {
+    let mut dial = Dial::default();
+
+    for (i, m) in moves.iter().enumerate() {
+        if m.distance < T::zero() {
+            bail!("Negative distance {} at move {i}", m.distance);
+        }
+        dial.turn(m).ok_or_else(|| anyhow!("Dial overflow at move {i}"))?;
+    }
+
+    Ok(dial)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use proptest::prelude::*;
+
+    fn a_move(max_distance: i64) -> impl Strategy<Value = Move<i64>> {
+        let direction = prop_oneof![Just(Direction::Left), Just(Direction::Right)];
+        (direction, 0..=max_distance).prop_map(|(direction, distance)| Move { direction, distance })
+    }
+
+    fn moves(max_distance: i64) -> impl Strategy<Value = Vec<Move<i64>>> {
+        prop::collection::vec(a_move(max_distance), 0..50)
+    }
+
+    // one click at a time, what `turn` has to agree with
+    fn click_by_click(moves: &[Move<i64>]) -> Dial<i64> {
+        let mut dial = Dial::<i64>::default();
+        for m in moves {
+            let step: i64 = if m.direction == Direction::Left { -1 } else { 1 };
+            for _ in 0..m.distance {
+                dial.position = (dial.position + step).rem_euclid(DIAL_SIZE as i64);
+                if dial.position == 0 {
+                    dial.passes_zero += 1;
+                }
+            }
+            if dial.position == 0 {
+                dial.ends_on_zero += 1;
+            }
+        }
+        dial
+    }
+
+    proptest! {
+        #[test]
+        fn prop_position_stays_on_dial(moves in moves(1_000_000)) {
+            for i in 0..=moves.len() {
+                let position = run_moves(&moves[..i]).unwrap().position;
+                prop_assert!((0..DIAL_SIZE as i64).contains(&position), "position {position} after {i} moves");
+            }
+        }
+
+        #[test]
+        fn prop_matches_clicks(moves in moves(350)) {
+            prop_assert_eq!(click_by_click(&moves), run_moves(&moves).unwrap());
+        }
+
+        #[test]
+        fn prop_split_move(moves in moves(1000), i in any::<prop::sample::Index>(), split in 0.0..=1.0f64) {
+            prop_assume!(!moves.is_empty());
+            // turning 250 is the same as turning 100 then 150, apart from where the dial stops
+            let i = i.index(moves.len());
+            let first = (moves[i].distance as f64 * split) as i64;
+            let mut split_moves = moves.clone();
+            split_moves[i].distance -= first;
+            split_moves.insert(i, Move { direction: moves[i].direction, distance: first });
+
+            let (dial, split_dial) = (run_moves(&moves).unwrap(), run_moves(&split_moves).unwrap());
+            prop_assert_eq!(dial.position, split_dial.position);
+            prop_assert_eq!(dial.passes_zero, split_dial.passes_zero);
+        }
+
+        #[test]
+        fn prop_parse_round_trip(m in a_move(i64::MAX)) {
+            let letter = if m.direction == Direction::Left { "L" } else { "R" };
+            prop_assert_eq!(m, parse_move::<i64>(&format!("{letter}{}", m.distance)).unwrap());
+        }
+    }
+
+    #[test]
+    fn test_parse_move() -> anyhow::Result<()> {
+        assert_eq!(Move { direction: Direction::Left, distance: 68 }, parse_move::<i32>("L68")?);
+        assert_eq!(Move { direction: Direction::Right, distance: 5 }, parse_move::<i64>("R5")?);
+
+        assert!(parse_move::<i32>("X5").is_err());
+        assert!(parse_move::<i32>("R").is_err());
+        assert!(parse_move::<i32>("").is_err());
+        assert!(parse_move::<i32>("R99999999999").is_err());
+        assert_eq!(99999999999, parse_move::<i64>("R99999999999")?.distance);
+        Ok(())
+    }
+
+    #[test]
+    fn test_run_moves_widths() -> anyhow::Result<()> {
+        let dial = run_moves(&[Move { direction: Direction::Right, distance: 250i32 }])?;
+        assert_eq!(Dial { position: 0, ends_on_zero: 1, passes_zero: 3 }, dial);
+
+        let dial = run_moves(&[Move { direction: Direction::Left, distance: 10_000_000_000_050i64 }])?;
+        assert_eq!(Dial { position: 0, ends_on_zero: 1, passes_zero: 100_000_000_001 }, dial);
+
+        let dial = run_moves(&[Move { direction: Direction::Right, distance: i128::MAX }])?;
+        assert_eq!(i128::MAX / 100, dial.passes_zero);
+        assert_eq!(77, dial.position);
+        Ok(())
+    }
+
+    #[test]
+    fn test_run_moves_overflow() {
+        let moves = vec![Move { direction: Direction::Right, distance: i32::MAX }; 200];
+
+        let err = run_moves(&moves).unwrap_err();
+        assert_eq!("Dial overflow at move 100", err.to_string());
+
+        let moves = moves.iter().map(|m| Move { direction: m.direction, distance: m.distance as i64 }).collect::<Vec<_>>();
+        assert!(run_moves(&moves).is_ok());
+    }
+
+    #[test]
+    fn test_run_moves_negative() {
+        let moves = vec![
+            Move { direction: Direction::Right, distance: 5 },
+            Move { direction: Direction::Left, distance: -5 },
+        ];
+
+        let err = run_moves(&moves).unwrap_err();
+        assert_eq!("Negative distance -5 at move 1", err.to_string());
+    }
+}
@@ -0,0 +1,46 @@
+use std::io::Write;
+
+use rand::{RngExt, SeedableRng};
+use rand_chacha::ChaCha8Rng;
+
+/// Writes `count` random moves, one per line like the puzzle input (`L68`), each turning the
+/// dial between 1 and `max_distance` clicks. The same seed always gives the same moves.
+pub fn generate_moves(out: &mut impl Write, count: usize, max_distance: u64, seed: u64) -> std::io::Result<()> {
+    let mut rng = ChaCha8Rng::seed_from_u64(seed);
+
+    for _ in 0..count {
+        let direction = if rng.random_bool(0.5) { 'L' } else { 'R' };
+        writeln!(out, "{direction}{}", rng.random_range(1..=max_distance.max(1)))?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dial::{parse_move, run_moves, Move};
+
+    fn generate(count: usize, max_distance: u64, seed: u64) -> anyhow::Result<String> {
+        let mut out = vec![];
+        generate_moves(&mut out, count, max_distance, seed)?;
+        Ok(String::from_utf8(out)?)
+    }
+
+    #[test]
+    fn test_generate_moves() -> anyhow::Result<()> {
+        let text = generate(500, 999, 7)?;
+        let moves = text.lines().map(parse_move::<i64>).collect::<anyhow::Result<Vec<Move<i64>>>>()?;
+
+        assert_eq!(500, moves.len());
+        assert!(moves.iter().all(|m| (1..=999).contains(&m.distance)));
+        run_moves(&moves)?;
+        Ok(())
+    }
+
+    #[test]
+    fn test_reproducible() -> anyhow::Result<()> {
+        assert_eq!(generate(100, 999, 42)?, generate(100, 999, 42)?);
+        assert_ne!(generate(100, 999, 42)?, generate(100, 999, 43)?);
+        Ok(())
+    }
+}
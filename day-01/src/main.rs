@@ -0,0 +1,66 @@
+use day_01::generate::generate_moves;
+use day_01::part1::process as process_part1;
+use day_01::part2::process as process_part2;
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::PathBuf;
+use anyhow::Context;
+use clap::{Parser, Subcommand};
+
+/// Counts how often the safe's dial points at 0
+#[derive(Parser, Debug)]
+#[command(version, about, long_about = None)]
+struct Args {
+    /// Puzzle part, 1 counts the moves that end on 0 and 2 every click that lands on it
+    #[arg(short, long, default_value_t = 1)]
+    part: usize,
+
+    #[arg(short, long, default_value = "../inputs/day1_part1.csv")]
+    input: PathBuf,
+
+    #[command(subcommand)]
+    command: Option<Command>,
+}
+
+#[derive(Subcommand, Debug)]
+enum Command {
+    /// Write a random list of moves, the same seed gives the same list
+    Generate {
+        /// Number of moves
+        #[arg(short, long, default_value_t = 4000)]
+        moves: usize,
+
+        /// Most clicks a single move turns the dial
+        #[arg(long, default_value_t = 999)]
+        max_distance: u64,
+
+        #[arg(short, long, default_value_t = 1)]
+        seed: u64,
+
+        /// File to write to instead of stdout
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+    },
+}
+
+fn main() -> anyhow::Result<()> {
+    let args = Args::parse();
+
+    if let Some(Command::Generate { moves, max_distance, seed, output }) = args.command {
+        let mut out: Box<dyn Write> = match &output {
+            Some(path) => Box::new(BufWriter::new(File::create(path).with_context(|| format!("Failed to create {}", path.display()))?)),
+            None => Box::new(BufWriter::new(std::io::stdout().lock())),
+        };
+        generate_moves(&mut out, moves, max_distance, seed)?;
+        out.flush()?;
+        return Ok(())
+    }
+
+    let (zeros, position) = match args.part {
+        1 => process_part1(&args.input)?,
+        2 => process_part2(&args.input)?,
+        part => anyhow::bail!("Unknown part {part}"),
+    };
+    println!("{zeros} (the dial ends on {position})");
+    Ok(())
+}
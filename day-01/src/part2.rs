@@ -1,83 +1,32 @@
-use std::fs::File;
+use std::path::Path;
 
-pub enum Direction {
-    Left,
-    Right,
-}
-
-struct Move {
-    direction: Direction,
-    distance: i32,
-}
+use crate::dial::{read_moves, run_moves, DialInt, Move};
 
 #[tracing::instrument]
-pub fn process() -> anyhow::Result<(i32, i32)> {
-    let file = File::open("./../inputs/day1_part1.csv")?;
-    let mut reader = csv::ReaderBuilder::new().has_headers(false).from_reader(file);
-
-    let turns = reader.records().map(|r| {
-        let record = r.unwrap().as_slice().to_owned();
-        let split_record = &record.split_at(1);
-        let direction = match split_record.0 {
-            "L" => Direction::Left,
-            "R" => Direction::Right,
-            _ => panic!("Invalid direction"),
-        };
-        let distance: i32 = split_record.1.parse().unwrap();
-        Move { direction, distance }
-    }).collect::<Vec<Move>>();
-
-    let (n_zeros, dial) = process_moves(turns);
+pub fn process(input: &Path) -> anyhow::Result<(i64, i64)> {
+    let turns = read_moves(input)?;
+
+    let (n_zeros, dial) = process_moves(turns)?;
 
     Ok((n_zeros, dial))
 }
 
-fn process_moves(moves: Vec<Move>) -> (i32, i32) {
-    let mut dial = 50;
-    let mut n_zeros = 0;
-
-    moves.iter().for_each(|m| {
-        match m.direction {
-            Direction::Left => {
-                let full_rotations = m.distance / 100;
-                n_zeros += full_rotations;
-                let dial_was = dial;
-
-                dial -= m.distance % 100;
-                if dial < 0 {
-                    if dial_was != 0 {
-                        n_zeros += 1
-                    }
-                    dial += 100;
-                }
-            },
-            Direction::Right => {
-                let full_rotations = m.distance / 100;
-                n_zeros += full_rotations;
-                dial += m.distance % 100;
-
-                if dial > 100 {
-                    n_zeros += 1
-                }
-                if dial > 99 {
-                    dial -= 100;
-                }
-            }
-        };
-        if dial == 0 { n_zeros += 1; }
-        println!("{n_zeros}, {dial}")
-    });
-    (n_zeros, dial)
+fn process_moves<T: DialInt>(moves: Vec<Move<T>>) -> anyhow::Result<(T, T)> {
+    let dial = run_moves(&moves)?;
+
+    Ok((dial.passes_zero, dial.position))
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::dial::Direction;
 
     #[test]
     fn test_process() -> anyhow::Result<()> {
-        // assert_eq!("", process(input)?);
-        let (z, d) = process()?;
+        let input = Path::new("../inputs/day1_part1.csv");
+
+        let (z, _) = process(input)?;
         assert_eq!(6412, z);
         Ok(())
     }
@@ -109,140 +58,152 @@ mod tests {
             Move { direction: Direction::Left, distance: 82 },
         ];
         // assert_eq!("", process(input)?);
-        let (z, d) = process_moves(moves);
+        let (z, _) = process_moves(moves)?;
         assert_eq!(6, z);
         Ok(())
     }
 
     #[test]
-    fn test_process_moves_right_49() {
+    fn test_process_moves_right_49() -> anyhow::Result<()> {
         let moves = vec![
             Move { direction: Direction::Right, distance: 49 },
         ];
 
-        let (z, d) = process_moves(moves);
+        let (z, d) = process_moves(moves)?;
         assert_eq!(99, d);
         assert_eq!(0, z);
+        Ok(())
     }
 
     #[test]
-    fn test_process_moves_right_51() {
+    fn test_process_moves_right_51() -> anyhow::Result<()> {
         let moves = vec![
             Move { direction: Direction::Right, distance: 51}
         ];
 
-        let (z, d) = process_moves(moves);
+        let (z, d) = process_moves(moves)?;
         assert_eq!(1, d);
         assert_eq!(1, z);
+        Ok(())
     }
 
     #[test]
-    fn test_process_moves_right_151() {
+    fn test_process_moves_right_151() -> anyhow::Result<()> {
         let moves = vec![
             Move { direction: Direction::Right, distance: 151}
         ];
 
-        let (z, d) = process_moves(moves);
+        let (z, d) = process_moves(moves)?;
         assert_eq!(1, d);
         assert_eq!(2, z);
+        Ok(())
     }
 
     #[test]
-    fn test_process_moves_right_50() {
+    fn test_process_moves_right_50() -> anyhow::Result<()> {
         let moves = vec![
             Move { direction: Direction::Right, distance: 50 },
         ];
 
-        let (z, d) = process_moves(moves);
+        let (z, d) = process_moves(moves)?;
         assert_eq!(0, d);
         assert_eq!(1, z);
+        Ok(())
     }
 
     #[test]
-    fn test_process_moves_right_150() {
+    fn test_process_moves_right_150() -> anyhow::Result<()> {
         let moves = vec![
             Move { direction: Direction::Right, distance: 150 },
         ];
 
-        let (z, d) = process_moves(moves);
+        let (z, d) = process_moves(moves)?;
         assert_eq!(0, d);
         assert_eq!(2, z);
+        Ok(())
     }
 
     #[test]
-    fn test_process_moves_right_149() {
+    fn test_process_moves_right_149() -> anyhow::Result<()> {
         let moves = vec![
             Move { direction: Direction::Right, distance: 149 },
         ];
 
-        let (z, d) = process_moves(moves);
+        let (z, d) = process_moves(moves)?;
         assert_eq!(99, d);
         assert_eq!(1, z);
+        Ok(())
     }
 
     #[test]
-    fn test_process_moves_left_50() {
+    fn test_process_moves_left_50() -> anyhow::Result<()> {
         let moves = vec![
             Move { direction: Direction::Left, distance: 50 },
         ];
 
-        let (z, d) = process_moves(moves);
+        let (z, d) = process_moves(moves)?;
         assert_eq!(0, d);
         assert_eq!(1, z);
+        Ok(())
     }
 
     #[test]
-    fn test_process_moves_left_49() {
+    fn test_process_moves_left_49() -> anyhow::Result<()> {
         let moves = vec![
             Move { direction: Direction::Left, distance: 49 },
         ];
 
-        let (z, d) = process_moves(moves);
+        let (z, d) = process_moves(moves)?;
         assert_eq!(1, d);
         assert_eq!(0, z);
+        Ok(())
     }
 
     #[test]
-    fn test_process_moves_left_51() {
+    fn test_process_moves_left_51() -> anyhow::Result<()> {
         let moves = vec![
             Move { direction: Direction::Left, distance: 51 },
         ];
 
-        let (z, d) = process_moves(moves);
+        let (z, d) = process_moves(moves)?;
         assert_eq!(99, d);
         assert_eq!(1, z);
+        Ok(())
     }
 
     #[test]
-    fn test_process_moves_left_150() {
+    fn test_process_moves_left_150() -> anyhow::Result<()> {
         let moves = vec![
             Move { direction: Direction::Left, distance: 150 },
         ];
 
-        let (z, d) = process_moves(moves);
+        let (z, d) = process_moves(moves)?;
         assert_eq!(0, d);
         assert_eq!(2, z);
+        Ok(())
     }
 
     #[test]
-    fn test_process_moves_left_149() {
+    fn test_process_moves_left_149() -> anyhow::Result<()> {
         let moves = vec![
             Move { direction: Direction::Left, distance: 149 },
         ];
 
-        let (z, d) = process_moves(moves);
+        let (z, d) = process_moves(moves)?;
         assert_eq!(1, d);
         assert_eq!(1, z);
+        Ok(())
     }
 
     #[test]
-    fn test_process_moves_left_151() {
+    fn test_process_moves_left_151() -> anyhow::Result<()> {
         let moves = vec![
             Move { direction: Direction::Left, distance: 151 },
         ];
 
-        let (z, d) = process_moves(moves);
+        let (z, d) = process_moves(moves)?;
         assert_eq!(99, d);
         assert_eq!(2, z);
+        Ok(())
     }
 }
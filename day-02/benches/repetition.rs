@@ -0,0 +1,40 @@
+use std::hint::black_box;
+use std::path::Path;
+
+use criterion::{criterion_group, criterion_main, Criterion};
+use day_02::invalid::smallest_repeating_block;
+use day_02::part2::{process_input, split_string};
+
+// the original part2 check, one String and a HashSet per chunk size
+fn is_valid_id_str(id: usize) -> bool {
+    let id_str = id.to_string();
+
+    for chunk_size in 1..=(id_str.len() / 2) {
+        if id_str.len().is_multiple_of(chunk_size) && split_string(&id_str, chunk_size).len() == 1 {
+            return false
+        }
+    }
+    true
+}
+
+fn scan(ranges: &[(usize, usize)], is_valid_id: fn(usize) -> bool) -> usize {
+    ranges.iter().map(|(min, max)| (*min..=*max).filter(|id| !is_valid_id(*id)).sum::<usize>()).sum()
+}
+
+fn bench_repetition(c: &mut Criterion) {
+    let text = std::fs::read_to_string(Path::new("../inputs/day2_part1.csv")).unwrap();
+    let ranges = process_input(text).unwrap();
+
+    let mut group = c.benchmark_group("day-02 part2 full input");
+    group.sample_size(10);
+
+    group.bench_function("string chunks", |b| b.iter(|| scan(black_box(&ranges), is_valid_id_str)));
+    group.bench_function("arithmetic", |b| {
+        b.iter(|| scan(black_box(&ranges), |id| smallest_repeating_block(&id, 10).is_none()))
+    });
+
+    group.finish();
+}
+
+criterion_group!(benches, bench_repetition);
+criterion_main!(benches);
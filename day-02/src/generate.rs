@@ -0,0 +1,81 @@
+use std::io::Write;
+
+use rand::seq::SliceRandom;
+use rand::{RngExt, SeedableRng};
+use rand_chacha::ChaCha8Rng;
+
+/// Writes `count` random id ranges on one line like the puzzle input (`11-22,95-115`). Each
+/// range starts at an id of 1 to `max_digits` digits (at most 19) and holds up to `max_len`
+/// more ids. The ranges don't overlap and come in no particular order. The same seed always
+/// gives the same ranges.
+pub fn generate_ranges(out: &mut impl Write, count: usize, max_digits: u32, max_len: u64, seed: u64) -> std::io::Result<()> {
+    let mut rng = ChaCha8Rng::seed_from_u64(seed);
+    let max_digits = max_digits.clamp(1, 19);
+
+    let mut mins = (0..count).map(|_| {
+        let digits = rng.random_range(1..=max_digits);
+        rng.random_range(10u64.pow(digits - 1)..10u64.pow(digits))
+    }).collect::<Vec<u64>>();
+    mins.sort_unstable();
+
+    // push each range past the one before it so none of them overlap
+    let mut ranges: Vec<(u64, u64)> = Vec::with_capacity(count);
+    for min in mins {
+        let min = match ranges.last() {
+            Some((_, u64::MAX)) => break,
+            Some((_, prev)) if min <= *prev => prev + 1,
+            _ => min,
+        };
+        ranges.push((min, min.saturating_add(rng.random_range(0..=max_len))));
+    }
+    ranges.shuffle(&mut rng);
+
+    let line = ranges.iter().map(|(min, max)| format!("{min}-{max}")).collect::<Vec<String>>().join(",");
+    writeln!(out, "{line}")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::radix::parse_ranges;
+    use crate::ranges::IdRangeSet;
+    use crate::rules::{sum_invalid_ids, RepeatedExactly};
+
+    fn generate(count: usize, max_digits: u32, max_len: u64, seed: u64) -> anyhow::Result<String> {
+        let mut out = vec![];
+        generate_ranges(&mut out, count, max_digits, max_len, seed)?;
+        Ok(String::from_utf8(out)?)
+    }
+
+    #[test]
+    fn test_generate_ranges() -> anyhow::Result<()> {
+        let text = generate(200, 10, 5000, 7)?;
+        let mut ranges = parse_ranges::<u64>(&text, 10)?;
+
+        assert_eq!(200, ranges.len());
+        assert!(ranges.iter().all(|(min, max)| max - min <= 5000 && *max < 10u64.pow(10) + 200 * 5001));
+        ranges.sort();
+        assert!(ranges.windows(2).all(|w| w[0].1 < w[1].0));
+
+        sum_invalid_ids(&IdRangeSet::from(ranges), &RepeatedExactly(2), 10)?;
+        Ok(())
+    }
+
+    #[test]
+    fn test_crowded_ranges() -> anyhow::Result<()> {
+        // far more ranges than there are 1 digit ids, they get pushed up past 9
+        let ranges = parse_ranges::<u64>(&generate(50, 1, 3, 1)?, 10)?;
+        assert_eq!(50, ranges.len());
+
+        let ranges = parse_ranges::<u64>(&generate(5, 19, u64::MAX, 1)?, 10)?;
+        assert!(!ranges.is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn test_reproducible() -> anyhow::Result<()> {
+        assert_eq!(generate(100, 12, 1000, 42)?, generate(100, 12, 1000, 42)?);
+        assert_ne!(generate(100, 12, 1000, 42)?, generate(100, 12, 1000, 43)?);
+        Ok(())
+    }
+}
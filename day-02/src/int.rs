@@ -0,0 +1,113 @@
+use std::fmt::{Debug, Display};
+use std::hash::Hash;
+
+use num_bigint::BigUint;
+use num_integer::Integer;
+use num_traits::{CheckedAdd, CheckedMul, CheckedSub, FromPrimitive, ToPrimitive};
+
+/// Integer types ids, counts and sums can be worked out in
+pub trait IdInt:
+    Integer + Clone + Debug + Display + Hash + Send + Sync
+    + CheckedAdd + CheckedSub + CheckedMul + FromPrimitive + ToPrimitive + 'static
+{
+    /// Name used in error messages and for `--width`
+    const NAME: &'static str;
+}
+
+impl IdInt for usize {
+    const NAME: &'static str = "usize";
+}
+
+impl IdInt for u64 {
+    const NAME: &'static str = "u64";
+}
+
+impl IdInt for u128 {
+    const NAME: &'static str = "u128";
+}
+
+impl IdInt for BigUint {
+    const NAME: &'static str = "big";
+}
+
+/// A value, or a sum, that is too big for the integer type it's being worked out in
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Overflow {
+    pub width: &'static str,
+    pub what: String,
+}
+
+impl Display for Overflow {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} doesn't fit in {}", self.what, self.width)
+    }
+}
+
+impl std::error::Error for Overflow {}
+
+pub fn overflow<T: IdInt>(what: &str) -> anyhow::Error {
+    Overflow { width: T::NAME, what: what.to_string() }.into()
+}
+
+pub fn from_u32<T: IdInt>(value: u32) -> T {
+    T::from_u32(value).expect("every id type holds a u32")
+}
+
+/// radix^exp, or None if it doesn't fit
+pub fn checked_pow<T: IdInt>(radix: u32, exp: u32) -> Option<T> {
+    let radix = from_u32::<T>(radix);
+    (0..exp).try_fold(T::one(), |acc, _| acc.checked_mul(&radix))
+}
+
+/// lowest + (lowest + 1) + ... + highest, or None if it doesn't fit
+pub fn series_sum<T: IdInt>(lowest: &T, highest: &T) -> Option<T> {
+    if lowest > highest {
+        return Some(T::zero())
+    }
+    let n = highest.clone() - lowest.clone() + T::one();
+    let ends = lowest.checked_add(highest)?;
+    let two = from_u32::<T>(2);
+
+    // halve whichever of the two is even so the division is exact
+    if n.is_even() {
+        (n / two).checked_mul(&ends)
+    } else {
+        (ends / two).checked_mul(&n)
+    }
+}
+
+/// min, min + 1, ..., max
+pub fn id_range<T: IdInt>(min: T, max: T) -> impl Iterator<Item = T> {
+    let first = (min <= max).then_some(min);
+    std::iter::successors(first, move |id| (id < &max).then(|| id.clone() + T::one()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_checked_pow() {
+        assert_eq!(Some(1000u64), checked_pow(10, 3));
+        assert_eq!(Some(10_000_000_000_000_000_000u64), checked_pow(10, 19));
+        assert_eq!(None, checked_pow::<u64>(10, 20));
+        assert_eq!(Some(100_000_000_000_000_000_000u128), checked_pow(10, 20));
+        assert_eq!(BigUint::from(10u32).pow(50), checked_pow(10, 50).unwrap());
+    }
+
+    #[test]
+    fn test_series_sum() {
+        assert_eq!(Some(55usize), series_sum(&1, &10));
+        assert_eq!(Some(7usize), series_sum(&7, &7));
+        assert_eq!(Some(0usize), series_sum(&8, &7));
+        assert_eq!(None, series_sum(&1u64, &u64::MAX));
+        assert_eq!(Some(u64::MAX as u128 * (u64::MAX as u128 + 1) / 2), series_sum(&1u128, &(u64::MAX as u128)));
+    }
+
+    #[test]
+    fn test_id_range() {
+        assert_eq!(vec![3usize, 4, 5], id_range(3, 5).collect::<Vec<_>>());
+        assert_eq!(vec![u64::MAX], id_range(u64::MAX, u64::MAX).collect::<Vec<_>>());
+        assert_eq!(0, id_range(5usize, 3).count());
+    }
+}
@@ -0,0 +1,400 @@
+/*
+Instead of checking every id in a range, build the invalid ids directly.
+
+An id of `total_len` digits made of a `block_len` digit block repeated is
+    block * (radix^total_len - 1) / (radix^block_len - 1)
+e.g. 123123 = 123 * 1001 in base 10. So for every length in the range and every block length
+dividing it we only need the range of blocks that lands inside [min, max],
+and the sum of those is an arithmetic series.
+
+Everything is generic over the id type. Sums and counts come back as None when
+they don't fit in it.
+ */
+
+use std::iter::Peekable;
+
+use crate::int::{checked_pow, from_u32, id_range, series_sum, IdInt};
+
+pub fn n_digits<T: IdInt>(value: &T, radix: u32) -> u32 {
+    let radix = from_u32::<T>(radix);
+    let mut value = value.clone();
+    let mut n_digits = 1;
+
+    while value >= radix {
+        value = value / radix.clone();
+        n_digits += 1;
+    }
+
+    n_digits
+}
+
+/// The multiplier turning a block into the repeated id, e.g. (6, 3, 10) -> 1001.
+/// Built as 1 + radix^block_len + radix^(2 * block_len) + ... so it only
+/// overflows if no id of `total_len` digits fits either.
+pub fn repeat_multiplier<T: IdInt>(total_len: u32, block_len: u32, radix: u32) -> Option<T> {
+    (0..total_len / block_len).try_fold(T::zero(), |acc, i| {
+        acc.checked_add(&checked_pow(radix, i * block_len)?)
+    })
+}
+
+/// The shortest block that `id` is made of when repeated at least twice, as
+/// (block, block_len), or None if its digits don't repeat. Doesn't allocate for
+/// the fixed width types: the id is a repeated block exactly when it divides by
+/// the repeat multiplier.
+pub fn smallest_repeating_block<T: IdInt>(id: &T, radix: u32) -> Option<(T, u32)> {
+    let total_len = n_digits(id, radix);
+
+    (1..total_len)
+        .filter(|block_len| total_len.is_multiple_of(*block_len))
+        .find_map(|block_len| {
+            let multiplier = repeat_multiplier::<T>(total_len, block_len, radix)?;
+            id.is_multiple_of(&multiplier).then(|| (id.clone() / multiplier, block_len))
+        })
+}
+
+/// True if `id` is one block repeated exactly `times` times
+pub fn is_repeated_exactly<T: IdInt>(id: &T, times: u32, radix: u32) -> bool {
+    let total_len = n_digits(id, radix);
+
+    if times == 0 || !total_len.is_multiple_of(times) {
+        return false
+    }
+    repeat_multiplier::<T>(total_len, total_len / times, radix)
+        .is_some_and(|multiplier| id.is_multiple_of(&multiplier))
+}
+
+/// The blocks that give ids in [min, max] with `total_len` digits, as
+/// (lowest block, highest block, multiplier), or None if there aren't any.
+fn block_range<T: IdInt>(min: &T, max: &T, total_len: u32, block_len: u32, radix: u32) -> Option<(T, T, T)> {
+    // no id of this length fits in T
+    let multiplier = repeat_multiplier::<T>(total_len, block_len, radix)?;
+
+    // blocks can't start with a 0
+    let lowest_block = checked_pow::<T>(radix, block_len - 1)?.max(min.div_ceil(&multiplier));
+    let highest_block = match checked_pow::<T>(radix, block_len) {
+        Some(limit) => (limit - T::one()).min(max.clone() / multiplier.clone()),
+        None => max.clone() / multiplier.clone(),
+    };
+
+    (lowest_block <= highest_block).then_some((lowest_block, highest_block, multiplier))
+}
+
+/// Sum of all ids in [min, max] that have `total_len` digits and are made
+/// of a repeated `block_len` digit block.
+pub fn sum_repeated<T: IdInt>(min: &T, max: &T, total_len: u32, block_len: u32, radix: u32) -> Option<T> {
+    let Some((lowest_block, highest_block, multiplier)) = block_range(min, max, total_len, block_len, radix) else {
+        return Some(T::zero())
+    };
+
+    series_sum(&lowest_block, &highest_block)?.checked_mul(&multiplier)
+}
+
+/// Same as `sum_repeated`, but counts the ids
+pub fn count_repeated<T: IdInt>(min: &T, max: &T, total_len: u32, block_len: u32, radix: u32) -> Option<T> {
+    let count = block_range(min, max, total_len, block_len, radix)
+        .map_or(T::zero(), |(lowest_block, highest_block, _)| highest_block - lowest_block + T::one());
+    Some(count)
+}
+
+/// Same as `sum_repeated`, but lists the ids in increasing order
+pub fn repeated_ids<T: IdInt>(min: &T, max: &T, total_len: u32, block_len: u32, radix: u32) -> impl Iterator<Item = T> + use<T> {
+    let blocks = block_range(min, max, total_len, block_len, radix);
+
+    blocks.into_iter().flat_map(|(lowest_block, highest_block, multiplier)| {
+        id_range(lowest_block, highest_block).map(move |block| block * multiplier.clone())
+    })
+}
+
+// sum_repeated and count_repeated have the same shape
+type RepeatedFn<T> = fn(&T, &T, u32, u32, u32) -> Option<T>;
+
+/// Sum of all decimal ids in [min, max] that are a block repeated exactly twice
+pub fn sum_repeated_twice(min: usize, max: usize) -> usize {
+    sum_repeated_exactly(&min, &max, 2, 10).expect("sum doesn't fit in usize")
+}
+
+/// Sum of all ids in [min, max] that are a block repeated exactly `times` times
+pub fn sum_repeated_exactly<T: IdInt>(min: &T, max: &T, times: u32, radix: u32) -> Option<T> {
+    exactly_by(min, max, times, radix, sum_repeated)
+}
+
+pub fn count_repeated_exactly<T: IdInt>(min: &T, max: &T, times: u32, radix: u32) -> Option<T> {
+    exactly_by(min, max, times, radix, count_repeated)
+}
+
+pub fn ids_repeated_exactly<T: IdInt>(min: &T, max: &T, times: u32, radix: u32) -> impl Iterator<Item = T> + use<T> {
+    let (min, max) = (min.clone(), max.clone());
+
+    (n_digits(&min, radix)..=n_digits(&max, radix))
+        .filter(move |total_len| total_len.is_multiple_of(times))
+        .flat_map(move |total_len| repeated_ids(&min, &max, total_len, total_len / times, radix))
+}
+
+fn exactly_by<T: IdInt>(min: &T, max: &T, times: u32, radix: u32, per_length: RepeatedFn<T>) -> Option<T> {
+    (n_digits(min, radix)..=n_digits(max, radix))
+        .filter(|total_len| total_len.is_multiple_of(times))
+        .try_fold(T::zero(), |acc, total_len| acc.checked_add(&per_length(min, max, total_len, total_len / times, radix)?))
+}
+
+/// Sum of all decimal ids in [min, max] that are a block repeated at least twice
+pub fn sum_repeated_at_least_twice(min: usize, max: usize) -> usize {
+    sum_repeated_at_least(&min, &max, 2, 10).expect("sum doesn't fit in usize")
+}
+
+/// Sum of all ids in [min, max] that are a block repeated at least `times` times
+pub fn sum_repeated_at_least<T: IdInt>(min: &T, max: &T, times: u32, radix: u32) -> Option<T> {
+    at_least_by(min, max, times, radix, sum_repeated)
+}
+
+pub fn count_repeated_at_least<T: IdInt>(min: &T, max: &T, times: u32, radix: u32) -> Option<T> {
+    at_least_by(min, max, times, radix, count_repeated)
+}
+
+/// Lists the ids in [min, max] that are a block repeated at least `times` times,
+/// in increasing order and without duplicates
+pub fn ids_repeated_at_least<T: IdInt>(min: &T, max: &T, times: u32, radix: u32) -> impl Iterator<Item = T> + use<T> {
+    let (min, max) = (min.clone(), max.clone());
+
+    (n_digits(&min, radix)..=n_digits(&max, radix)).flat_map(move |total_len| {
+        let streams = (1..=total_len)
+            .filter(|b| total_len.is_multiple_of(*b) && total_len / b >= times)
+            .map(|b| repeated_ids(&min, &max, total_len, b, radix).peekable())
+            .collect::<Vec<_>>();
+        MergeDedup { streams }
+    })
+}
+
+fn at_least_by<T: IdInt>(min: &T, max: &T, times: u32, radix: u32, per_block: RepeatedFn<T>) -> Option<T> {
+    let mut total = T::zero();
+
+    for total_len in n_digits(min, radix)..=n_digits(max, radix) {
+        /*
+        An id whose shortest block has length p shows up for every block length
+        that is a multiple of p, e.g. 111111 is counted for blocks 1, 2 and 3.
+        So work out the total for the ids whose shortest block is exactly p
+        (mobius inversion over the divisors of p) and add those up for every p
+        that fits inside an allowed block length.
+        The ids are unsigned so the positive and negative terms are kept apart,
+        the difference is never negative.
+         */
+        let divisors = (1..=total_len).filter(|d| total_len.is_multiple_of(*d)).collect::<Vec<u32>>();
+        let allowed = divisors.iter().filter(|b| total_len / **b >= times).collect::<Vec<_>>();
+
+        let mut added = T::zero();
+        let mut removed = T::zero();
+        for p in &divisors {
+            if !allowed.iter().any(|b| b.is_multiple_of(*p)) {
+                continue
+            }
+            for d in divisors.iter().filter(|d| p.is_multiple_of(**d)) {
+                match mobius(p / d) {
+                    1 => added = added.checked_add(&per_block(min, max, total_len, *d, radix)?)?,
+                    -1 => removed = removed.checked_add(&per_block(min, max, total_len, *d, radix)?)?,
+                    _ => {}
+                }
+            }
+        }
+        total = total.checked_add(&(added - removed))?;
+    }
+
+    Some(total)
+}
+
+// merges sorted streams of ids, dropping the ids that show up in more than one
+struct MergeDedup<T: IdInt, I: Iterator<Item = T>> {
+    streams: Vec<Peekable<I>>,
+}
+
+impl<T: IdInt, I: Iterator<Item = T>> Iterator for MergeDedup<T, I> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<T> {
+        let lowest = self.streams.iter_mut().filter_map(|s| s.peek().cloned()).min()?;
+
+        for stream in self.streams.iter_mut() {
+            stream.next_if_eq(&lowest);
+        }
+
+        Some(lowest)
+    }
+}
+
+pub fn mobius(n: u32) -> i32 {
+    let mut n = n;
+    let mut result = 1;
+    let mut p = 2;
+
+    while p * p <= n {
+        if n.is_multiple_of(p) {
+            n /= p;
+            if n.is_multiple_of(p) {
+                return 0
+            }
+            result = -result;
+        }
+        p += 1;
+    }
+    if n > 1 {
+        result = -result;
+    }
+
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{part1, part2};
+    use num_bigint::BigUint;
+
+    const SAMPLE: [(usize, usize); 11] = [
+        (11, 22), (95, 115), (998, 1012), (1188511880, 1188511890), (222220, 222224),
+        (1698522, 1698528), (446443, 446449), (38593856, 38593862), (565653, 565659),
+        (824824821, 824824827), (2121212118, 2121212124),
+    ];
+
+    fn brute_force(min: usize, max: usize, is_valid_id: fn(usize) -> bool) -> usize {
+        (min..=max).filter(|id| !is_valid_id(*id)).sum()
+    }
+
+    #[test]
+    fn test_repeat_multiplier() {
+        assert_eq!(Some(11usize), repeat_multiplier(2, 1, 10));
+        assert_eq!(Some(1001usize), repeat_multiplier(6, 3, 10));
+        assert_eq!(Some(10101usize), repeat_multiplier(6, 2, 10));
+        assert_eq!(Some(111111usize), repeat_multiplier(6, 1, 10));
+    }
+
+    #[test]
+    fn test_mobius() {
+        let res = (1..=10).map(mobius).collect::<Vec<i32>>();
+        assert_eq!(res, vec![1, -1, -1, 0, -1, 1, -1, 0, 0, 1]);
+    }
+
+    #[test]
+    fn test_sample() {
+        let part1_sum: usize = SAMPLE.iter().map(|(min, max)| sum_repeated_twice(*min, *max)).sum();
+        assert_eq!(1227775554, part1_sum);
+
+        let part2_sum: usize = SAMPLE.iter().map(|(min, max)| sum_repeated_at_least_twice(*min, *max)).sum();
+        assert_eq!(4174379265, part2_sum);
+    }
+
+    #[test]
+    fn test_matches_brute_force() {
+        for (min, max) in [(1, 1), (1, 9), (1, 10_000), (9, 12), (95, 115), (998, 1012), (100_000, 123_456)] {
+            assert_eq!(brute_force(min, max, part1::is_valid_id), sum_repeated_twice(min, max), "{min}-{max}");
+            assert_eq!(brute_force(min, max, part2::is_valid_id), sum_repeated_at_least_twice(min, max), "{min}-{max}");
+        }
+    }
+
+    #[test]
+    fn test_repeated_times() {
+        assert_eq!(Some(111 + 222 + 333), sum_repeated_exactly(&100usize, &400, 3, 10));
+        assert_eq!(Some(111_111), sum_repeated_exactly(&111_111usize, &111_111, 3, 10));
+        assert_eq!(Some(111_111), sum_repeated_exactly(&111_111usize, &111_111, 6, 10));
+        assert_eq!(Some(0), sum_repeated_exactly(&111_111usize, &111_111, 4, 10));
+
+        // 1212 is only repeated twice, 1111 four times
+        assert_eq!(Some(1111), sum_repeated_at_least(&1111usize, &1212, 3, 10));
+        assert_eq!(Some(1111 + 1212), sum_repeated_at_least(&1111usize, &1212, 2, 10));
+        assert_eq!(Some(0), sum_repeated_at_least(&1usize, &1_000_000, 8, 10));
+    }
+
+    #[test]
+    fn test_smallest_repeating_block() {
+        assert_eq!(Some((1, 1)), smallest_repeating_block(&11usize, 10));
+        assert_eq!(Some((1, 1)), smallest_repeating_block(&111111usize, 10));
+        assert_eq!(Some((12, 2)), smallest_repeating_block(&121212usize, 10));
+        assert_eq!(Some((11885, 5)), smallest_repeating_block(&1188511885usize, 10));
+        assert_eq!(Some((0x1f, 2)), smallest_repeating_block(&0x1f1fusize, 16));
+        assert_eq!(None, smallest_repeating_block(&7usize, 10));
+        assert_eq!(None, smallest_repeating_block(&1213usize, 10));
+        assert_eq!(Some((100, 3)), smallest_repeating_block(&100100usize, 10));
+
+        for id in 1..20_000 {
+            // the string based check from part2
+            let id_str = id.to_string();
+            let repeated = (1..=id_str.len() / 2)
+                .any(|size| id_str.len().is_multiple_of(size) && part2::split_string(&id_str, size).len() == 1);
+
+            assert_eq!(repeated, smallest_repeating_block(&id, 10).is_some(), "{id}");
+            assert_eq!(!part1::is_valid_id(id), is_repeated_exactly(&id, 2, 10), "{id}");
+        }
+    }
+
+    #[test]
+    fn test_ids_and_counts() {
+        for (min, max) in [(1, 1000), (95, 115), (1000, 1212), (9_000, 130_000)] {
+            for times in [2, 3] {
+                let exactly = (min..=max).filter(|id| is_repeated_exactly(id, times, 10)).collect::<Vec<_>>();
+                assert_eq!(exactly, ids_repeated_exactly(&min, &max, times, 10).collect::<Vec<_>>());
+                assert_eq!(Some(exactly.len()), count_repeated_exactly(&min, &max, times, 10));
+
+                let at_least = (min..=max).filter(|id| {
+                    smallest_repeating_block(id, 10).is_some_and(|(_, b)| n_digits(id, 10) / b >= times)
+                }).collect::<Vec<_>>();
+                assert_eq!(at_least, ids_repeated_at_least(&min, &max, times, 10).collect::<Vec<_>>());
+                assert_eq!(Some(at_least.len()), count_repeated_at_least(&min, &max, times, 10));
+            }
+        }
+    }
+
+    #[test]
+    fn test_other_radix() {
+        // 0b1010 and 0b1111 in binary, 0x1212 in hex
+        assert_eq!(Some(0b1010 + 0b1111), sum_repeated_exactly(&0b1000usize, &0b1111, 2, 2));
+        assert_eq!(Some(0b11 + 0b1010 + 0b1111), sum_repeated_exactly(&1usize, &0b1111, 2, 2));
+        assert_eq!(Some(0x1212), sum_repeated_exactly(&0x1200usize, &0x1300, 2, 16));
+        assert_eq!(Some(0b111 + 0b11), sum_repeated_at_least(&1usize, &0b111, 2, 2));
+
+        let brute_force = (1..5000).filter(|id| {
+            let digits = format!("{id:o}");
+            let (a, b) = digits.split_at(digits.len() / 2);
+            a == b
+        }).sum::<usize>();
+        assert_eq!(Some(brute_force), sum_repeated_exactly(&1usize, &4999, 2, 8));
+    }
+
+    #[test]
+    fn test_billion_wide_range() {
+        // 11 + 22 + ... + 99
+        assert_eq!(495, sum_repeated_twice(1, 99));
+        assert_eq!(495, sum_repeated_at_least_twice(1, 100));
+
+        // every 2k digit id made of a k digit block is block * (10^k + 1), so the ones up to
+        // 10 digits add up to a series per k
+        let closed_form = (1..=5u32).map(|k| {
+            let (lowest, highest) = (10usize.pow(k - 1), 10usize.pow(k) - 1);
+            (lowest + highest) * (highest - lowest + 1) / 2 * (10usize.pow(k) + 1)
+        }).sum::<usize>();
+        assert_eq!(495495949990950, closed_form);
+        assert_eq!(closed_form, sum_repeated_twice(1, 9_999_999_999));
+        // the same ids plus 111, 1111, 121212, ..., counted once each (checked by listing them all)
+        assert_eq!(496436544631305, sum_repeated_at_least_twice(1, 9_999_999_999));
+        assert_eq!(Some(101088), count_repeated_at_least(&1usize, &9_999_999_999, 2, 10));
+
+        // a billion wide range that starts and ends mid block
+        assert_eq!(19889999998011, sum_repeated_twice(1_234_567_890, 2_345_678_901));
+        assert_eq!(19907777775787, sum_repeated_at_least_twice(1_234_567_890, 2_345_678_901));
+    }
+
+    #[test]
+    fn test_wide_ids() {
+        // 20 digit ids don't fit in u64, their sum still fits in u128
+        let min = 10u128.pow(19);
+        let max = 10u128.pow(20) - 1;
+        assert_eq!(Some(9_000_000_000), count_repeated_exactly(&min, &max, 2, 10));
+        assert_eq!(Some(495000000004499999995500000000), sum_repeated_exactly(&min, &max, 2, 10));
+        // summing every id a type can hold overflows it
+        assert_eq!(None, sum_repeated_exactly(&0u64, &u64::MAX, 2, 10));
+        assert_eq!(None, sum_repeated_exactly(&0u128, &u128::MAX, 2, 10));
+
+        let big = |s: &str| s.parse::<BigUint>().unwrap();
+        let block = big("1234567890");
+        let id = big("12345678901234567890");
+        assert_eq!(Some((block, 10)), smallest_repeating_block(&id, 10));
+        assert_eq!(Some(id.clone()), sum_repeated_exactly(&(id.clone() - 1u32), &(id.clone() + 1u32), 2, 10));
+        assert_eq!(Some(big("111111111111111111111111111111")), sum_repeated_at_least(&big("111111111111111111111111111110"), &big("111111111111111111111111111112"), 30, 10));
+    }
+}
@@ -1,2 +1,11 @@
+pub mod generate;
+pub mod int;
+pub mod invalid;
+#[cfg(feature = "parallel")]
+pub mod parallel;
 pub mod part1;
 pub mod part2;
+pub mod query;
+pub mod radix;
+pub mod ranges;
+pub mod rules;
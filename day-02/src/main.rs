@@ -0,0 +1,156 @@
+use day_02::generate::generate_ranges;
+use day_02::int::{IdInt, Overflow};
+use day_02::query::{report_ranges, write_csv, write_table};
+use day_02::radix::{check_radix, format_id, parse_ranges};
+use day_02::ranges::IdRangeSet;
+use day_02::rules::{parse_rule, IdRule, RepeatedAtLeast, RepeatedExactly};
+use num_bigint::BigUint;
+use std::fs::{read_to_string, File};
+use std::io::{BufWriter, Write};
+use std::path::PathBuf;
+use anyhow::Context;
+use clap::{Parser, Subcommand, ValueEnum};
+
+/// Sums the invalid ids in a list of id ranges
+#[derive(Parser, Debug)]
+#[command(version, about, long_about = None)]
+struct Args {
+    /// Puzzle part, picks the rule when --rule isn't given
+    #[arg(short, long, default_value_t = 1)]
+    part: usize,
+
+    /// Rule for invalid ids, e.g. "exactly:2", "at-least:3", "palindrome", "digit-sum:7",
+    /// combined with "and(a,b)", "or(a,b)" and "not(a)"
+    #[arg(short, long)]
+    rule: Option<String>,
+
+    /// Base (2 to 36) the ids are written in, for both the input and the answer
+    #[arg(long, default_value_t = 10)]
+    radix: u32,
+
+    #[arg(short, long, default_value = "../inputs/day2_part1.csv")]
+    input: PathBuf,
+
+    /// Integer type the ids and sums are worked out in, auto picks the smallest that fits
+    #[arg(short, long, value_enum, default_value_t = Width::Auto)]
+    width: Width,
+
+    /// Size of the thread pool, needs the `parallel` feature
+    #[arg(short, long)]
+    threads: Option<usize>,
+
+    #[command(subcommand)]
+    command: Option<Command>,
+}
+
+#[derive(ValueEnum, Debug, Clone, Copy, PartialEq, Eq)]
+enum Width {
+    Auto,
+    U64,
+    U128,
+    Big,
+}
+
+#[derive(Subcommand, Debug)]
+enum Command {
+    /// Count and sum the invalid ids of every input range
+    Report {
+        /// Print CSV instead of a table
+        #[arg(long)]
+        csv: bool,
+
+        /// Also list the invalid ids and the rule that matched each
+        #[arg(long)]
+        ids: bool,
+    },
+    /// Write a random list of id ranges, the same seed gives the same list
+    Generate {
+        /// Number of ranges
+        #[arg(short, long, default_value_t = 40)]
+        ranges: usize,
+
+        /// Most digits the first id of a range has, up to 19
+        #[arg(long, default_value_t = 10)]
+        max_digits: u32,
+
+        /// Most ids a range holds after its first one
+        #[arg(long, default_value_t = 100_000)]
+        max_len: u64,
+
+        #[arg(short, long, default_value_t = 1)]
+        seed: u64,
+
+        /// File to write to instead of stdout
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+    },
+}
+
+fn main() -> anyhow::Result<()> {
+    let args = Args::parse();
+
+    if let Some(Command::Generate { ranges, max_digits, max_len, seed, output }) = &args.command {
+        let mut out: Box<dyn Write> = match output {
+            Some(path) => Box::new(BufWriter::new(File::create(path).with_context(|| format!("Failed to create {}", path.display()))?)),
+            None => Box::new(BufWriter::new(std::io::stdout().lock())),
+        };
+        generate_ranges(&mut out, *ranges, *max_digits, *max_len, *seed)?;
+        out.flush()?;
+        return Ok(())
+    }
+
+    let radix = check_radix(args.radix)?;
+    let text = read_to_string(&args.input).with_context(|| format!("Failed to read {}", args.input.display()))?;
+
+    let output = match args.width {
+        Width::U64 => run::<u64>(&args, &text, radix)?,
+        Width::U128 => run::<u128>(&args, &text, radix)?,
+        Width::Big => run::<BigUint>(&args, &text, radix)?,
+        // only move up to a wider type when the ids or the sums don't fit
+        Width::Auto => match run::<u64>(&args, &text, radix) {
+            Err(err) if err.is::<Overflow>() => match run::<u128>(&args, &text, radix) {
+                Err(err) if err.is::<Overflow>() => run::<BigUint>(&args, &text, radix)?,
+                output => output?,
+            },
+            output => output?,
+        },
+    };
+
+    print!("{output}");
+    Ok(())
+}
+
+// works out everything before printing so a run that overflows leaves no partial output
+fn run<T: IdInt>(args: &Args, text: &str, radix: u32) -> anyhow::Result<String> {
+    let rule: Box<dyn IdRule<T>> = match (&args.rule, args.part) {
+        (Some(spec), _) => parse_rule(spec)?,
+        (None, 1) => Box::new(RepeatedExactly(2)),
+        (None, 2) => Box::new(RepeatedAtLeast(2)),
+        (None, part) => anyhow::bail!("Unknown part {part}"),
+    };
+
+    let input = parse_ranges::<T>(text, radix)?;
+
+    if let Some(Command::Report { csv, ids }) = args.command {
+        let reports = report_ranges(&input, rule.as_ref(), radix)?;
+        let mut out = vec![];
+        if csv {
+            write_csv(&mut out, &reports, rule.as_ref(), radix, ids)?;
+        } else {
+            write_table(&mut out, &reports, rule.as_ref(), radix, ids)?;
+        }
+        return Ok(String::from_utf8(out)?)
+    }
+
+    let ranges = IdRangeSet::from(input);
+
+    #[cfg(feature = "parallel")]
+    let sum = day_02::parallel::sum_invalid_ids(&ranges, rule.as_ref(), radix, args.threads)?;
+    #[cfg(not(feature = "parallel"))]
+    let sum = match args.threads {
+        Some(_) => anyhow::bail!("--threads needs day-02 to be built with the parallel feature"),
+        None => day_02::rules::sum_invalid_ids(&ranges, rule.as_ref(), radix)?,
+    };
+
+    Ok(format!("{}\n", format_id(&sum, radix)))
+}
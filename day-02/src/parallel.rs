@@ -0,0 +1,89 @@
+use rayon::prelude::*;
+
+use crate::int::{overflow, IdInt};
+use crate::ranges::IdRangeSet;
+use crate::rules::IdRule;
+
+/// Ranges wider than this are cut up so one huge range doesn't end up on a single thread
+pub const CHUNK_LEN: usize = 1 << 20;
+
+/// Most pieces a single range is cut into, wider ranges get longer pieces
+pub const MAX_PIECES: usize = 256;
+
+/// Same as `rules::sum_invalid_ids`, spread over a thread pool. The pieces are summed
+/// with integer addition so the answer doesn't depend on the scheduling.
+/// `threads` of None lets rayon pick the pool size.
+pub fn sum_invalid_ids<T: IdInt>(ranges: &IdRangeSet<T>, rule: &dyn IdRule<T>, radix: u32, threads: Option<usize>) -> anyhow::Result<T> {
+    let mut builder = rayon::ThreadPoolBuilder::new();
+    if let Some(threads) = threads {
+        builder = builder.num_threads(threads);
+    }
+    let pool = builder.build()?;
+
+    // a rule with a formula answers a whole range at once, cutting it up only adds work
+    let pieces = match rule.is_arithmetic() {
+        true => ranges.ranges().to_vec(),
+        false => split(ranges),
+    };
+    let sum = pool.install(|| {
+        pieces.par_iter()
+            .map(|(min, max)| rule.sum_invalid(min, max, radix))
+            .try_reduce(T::zero, |a, b| a.checked_add(&b))
+    });
+
+    sum.ok_or_else(|| overflow::<T>("sum of the invalid ids"))
+}
+
+// cuts the ranges into pieces of at least CHUNK_LEN ids and at most MAX_PIECES per range
+fn split<T: IdInt>(ranges: &IdRangeSet<T>) -> Vec<(T, T)> {
+    let chunk_len = T::from_usize(CHUNK_LEN).expect("every id type holds CHUNK_LEN");
+    let max_pieces = T::from_usize(MAX_PIECES).expect("every id type holds MAX_PIECES");
+
+    ranges.iter().flat_map(|(min, max)| {
+        let piece_len = ((max.clone() - min.clone()) / max_pieces.clone() + T::one()).max(chunk_len.clone());
+        IdRangeSet::from(vec![(min.clone(), max.clone())]).split(&piece_len)
+    }).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rules::{self, parse_rule, Palindrome, RepeatedAtLeast, RepeatedExactly};
+
+    #[test]
+    fn test_matches_serial() -> anyhow::Result<()> {
+        let ranges: IdRangeSet = IdRangeSet::from(vec![(11, 22), (95, 115), (998, 1012), (1188511880, 1188511890), (1, 1_500_000)]);
+        let rules: Vec<Box<dyn IdRule>> = vec![Box::new(RepeatedExactly(2)), Box::new(RepeatedAtLeast(2)), Box::new(Palindrome)];
+
+        for rule in rules {
+            let serial = rules::sum_invalid_ids(&ranges, rule.as_ref(), 10)?;
+            for threads in [Some(1), Some(3), None] {
+                assert_eq!(serial, sum_invalid_ids(&ranges, rule.as_ref(), 10, threads)?, "{rule:?} {threads:?}");
+            }
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_wide_ranges() -> anyhow::Result<()> {
+        let rule = RepeatedExactly(2);
+        let ranges = IdRangeSet::from(vec![(1u128, 10u128.pow(15)), (10u128.pow(30), 10u128.pow(30) + 10u128.pow(25))]);
+        assert_eq!(rules::sum_invalid_ids(&ranges, &rule, 10)?, sum_invalid_ids(&ranges, &rule, 10, Some(4))?);
+
+        let everything = IdRangeSet::from(vec![(0, u128::MAX)]);
+        assert!(sum_invalid_ids(&everything, &rule, 10, Some(4)).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_split() {
+        let ranges = IdRangeSet::from(vec![(1u64, 10u64.pow(18)), (5, 5)]);
+        // (5, 5) merges into the first range, which is cut into MAX_PIECES pieces
+        assert_eq!(MAX_PIECES, split(&ranges).len());
+        assert_eq!(vec![(1, 10)], split(&IdRangeSet::from(vec![(1u64, 10)])));
+
+        assert!(IdRule::<u64>::is_arithmetic(&RepeatedExactly(2)));
+        assert!(!IdRule::<u64>::is_arithmetic(&Palindrome));
+        assert!(parse_rule::<u64>("not(at-least:2)").unwrap().is_arithmetic());
+    }
+}
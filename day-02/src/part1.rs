@@ -1,31 +1,20 @@
+use anyhow::Context;
 use std::fs::read_to_string;
 use std::path::Path;
 
-pub fn read_txt(path: &Path) -> Vec<String> {
-    let file_text = read_to_string(path).expect("Failed to read file");
+use crate::radix::parse_ranges;
+use crate::ranges::IdRangeSet;
+use crate::rules::{self, RepeatedExactly};
 
-    file_text.lines().map(|line| line.to_string()).collect()
-}
-
-pub fn process_input(data: String) -> Vec<(usize, usize)> {
-    let elements = data.split(',').map(|s| {
-        s.to_owned().clone()
-    }).collect::<Vec<String>>();
-
-    let pairs = elements.iter().map(|s| {
-        s.split('-').map(|v| v).collect::<Vec<&str>>()
-    }).map(|p| {
-        (p[0].parse::<usize>().unwrap(), p[1].parse::<usize>().unwrap())
-    }).collect::<Vec<(usize, usize)>>();
-
-    return pairs
+pub fn process_input(data: String) -> anyhow::Result<Vec<(usize, usize)>> {
+    parse_ranges(&data, 10)
 }
 
 pub fn is_valid_id(id: usize) -> bool {
     let id_str = id.to_string();
 
     // an odd length is automatically valid
-    if id_str.len() % 2 != 0 { return true }
+    if !id_str.len().is_multiple_of(2) { return true }
 
     let (half_1, half_2) = id_str.split_at(id_str.len() / 2);
 
@@ -35,26 +24,20 @@ pub fn is_valid_id(id: usize) -> bool {
     true
 }
 
+pub fn sum_invalid_ids(ranges: Vec<(usize, usize)>) -> anyhow::Result<usize> {
+    // overlapping ranges would count their shared ids twice
+    let input = IdRangeSet::from(ranges);
+
+    // build the invalid ids of each range directly instead of checking every id
+    rules::sum_invalid_ids(&input, &RepeatedExactly(2), 10)
+}
+
 #[tracing::instrument]
 pub fn process(path: &Path) -> anyhow::Result<usize> {
-    let text = read_txt(&path);
-    let row = text.iter().next().unwrap().clone();
-    let input = process_input(row);
-
-    // iterate through numbers in each range. 
-    // convert to string and split in half
-    // see if the halves are the same
-    let mut sum = 0;
-
-    input.iter().for_each(|(min, max)| {
-        for id in *min..=*max {
-            if !is_valid_id(id) {
-                sum += id
-            }
-        }
-    });
-
-    Ok(sum)
+    let text = read_to_string(path).with_context(|| format!("Failed to read {}", path.display()))?;
+    let input = process_input(text)?;
+
+    sum_invalid_ids(input)
 }
 
 #[cfg(test)]
@@ -73,7 +56,24 @@ mod tests {
     fn test_process_input() -> anyhow::Result<()> {
         let input = "11-22,95-115,998-1012,1188511880-1188511890,222220-222224,1698522-1698528,446443-446449,38593856-38593862,565653-565659,824824821-824824827,2121212118-2121212124".to_string();
 
-        assert_eq!(11, process_input(input).len());
+        assert_eq!(11, process_input(input)?.len());
+        Ok(())
+    }
+
+    #[test]
+    fn test_process_input_lines() -> anyhow::Result<()> {
+        let input = "# wrapped sample\n11-22, 95-115,\n998-1012\n\n".to_string();
+
+        assert_eq!(vec![(11, 22), (95, 115), (998, 1012)], process_input(input)?);
+        assert!(process_input("22-11".to_string()).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_sum_invalid_ids_overlapping() -> anyhow::Result<()> {
+        let input = vec![(10, 25), (20, 40), (100, 111), (41, 45)];
+
+        assert_eq!(11 + 22 + 33 + 44, sum_invalid_ids(input)?);
         Ok(())
     }
 
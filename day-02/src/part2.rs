@@ -1,28 +1,18 @@
+use anyhow::Context;
 use std::collections::HashSet;
 use std::fs::read_to_string;
 use std::path::Path;
 
-pub fn read_txt(path: &Path) -> Vec<String> {
-    let file_text = read_to_string(path).expect("Failed to read file");
+use crate::invalid::smallest_repeating_block;
+use crate::radix::parse_ranges;
+use crate::ranges::IdRangeSet;
+use crate::rules::{self, RepeatedAtLeast};
 
-    file_text.lines().map(|line| line.to_string()).collect()
+pub fn process_input(data: String) -> anyhow::Result<Vec<(usize, usize)>> {
+    parse_ranges(&data, 10)
 }
 
-pub fn process_input(data: String) -> Vec<(usize, usize)> {
-    let elements = data.split(',').map(|s| {
-        s.to_owned().clone()
-    }).collect::<Vec<String>>();
-
-    let pairs = elements.iter().map(|s| {
-        s.split('-').map(|v| v).collect::<Vec<&str>>()
-    }).map(|p| {
-        (p[0].parse::<usize>().unwrap(), p[1].parse::<usize>().unwrap())
-    }).collect::<Vec<(usize, usize)>>();
-
-    return pairs
-}
-
-pub fn split_string(id_str: &String, chunk_size: usize) -> HashSet<&str> {
+pub fn split_string(id_str: &str, chunk_size: usize) -> HashSet<&str> {
     // collect the substrings
     let mut chunks = HashSet::new();
     let mut start = 0;
@@ -39,42 +29,24 @@ pub fn split_string(id_str: &String, chunk_size: usize) -> HashSet<&str> {
 }
 
 pub fn is_valid_id(id: usize) -> bool {
-    let id_str = id.to_string();
-
-    for chunk_size in 1..=(id_str.len() / 2) {
-        // only check chunk lengths that fit
-        if id_str.len() % chunk_size == 0 {
-            let chunks = split_string(&id_str, chunk_size);
-            // we exit out with false if we encounter any invalid chunking
-            if chunks.len() == 1 {
-                return false
-            }
-        }
-    }
+    // checks the digits arithmetically, split_string does the same on the text
+    smallest_repeating_block(&id, 10).is_none()
+}
+
+pub fn sum_invalid_ids(ranges: Vec<(usize, usize)>) -> anyhow::Result<usize> {
+    // overlapping ranges would count their shared ids twice
+    let input = IdRangeSet::from(ranges);
 
-    return true
+    // build the invalid ids of each range directly instead of checking every id
+    rules::sum_invalid_ids(&input, &RepeatedAtLeast(2), 10)
 }
 
 #[tracing::instrument]
 pub fn process(path: &Path) -> anyhow::Result<usize> {
-    let text = read_txt(&path);
-    let row = text.iter().next().unwrap().clone();
-    let input = process_input(row);
-
-    // iterate through numbers in each range. 
-    // convert to string and split in half
-    // see if the halves are the same
-    let mut sum = 0;
-
-    input.iter().for_each(|(min, max)| {
-        for id in *min..=*max {
-            if !is_valid_id(id) {
-                sum += id
-            }
-        }
-    });
-
-    Ok(sum)
+    let text = read_to_string(path).with_context(|| format!("Failed to read {}", path.display()))?;
+    let input = process_input(text)?;
+
+    sum_invalid_ids(input)
 }
 
 #[cfg(test)]
@@ -89,6 +61,14 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn test_sum_invalid_ids_overlapping() -> anyhow::Result<()> {
+        let input = vec![(10, 25), (20, 40), (100, 111), (41, 45)];
+
+        assert_eq!(11 + 22 + 33 + 44 + 111, sum_invalid_ids(input)?);
+        Ok(())
+    }
+
     #[test]
     fn test_process_full() -> anyhow::Result<()> {
         let input = Path::new("./../inputs/day2_part1.csv");
@@ -101,23 +81,23 @@ mod tests {
     fn test_split_string() -> anyhow::Result<()> {
         let mut set1 = HashSet::new();
         set1.insert("1");
-        assert_eq!(set1, split_string(&"11".to_string(), 1));
+        assert_eq!(set1, split_string("11", 1));
 
         let mut set1 = HashSet::new();
         set1.insert("11");
         set1.insert("22");
         set1.insert("33");
-        assert_eq!(set1, split_string(&"112233".to_string(), 2));
+        assert_eq!(set1, split_string("112233", 2));
 
         let mut set1 = HashSet::new();
         set1.insert("1");
         set1.insert("2");
-        assert_eq!(set1, split_string(&"211".to_string(), 1));
+        assert_eq!(set1, split_string("211", 1));
 
         let mut set1 = HashSet::new();
         set1.insert("1");
         set1.insert("2");
-        assert_eq!(set1, split_string(&"112".to_string(), 1));
+        assert_eq!(set1, split_string("112", 1));
 
         Ok(())
     }
@@ -0,0 +1,173 @@
+use std::io::Write;
+
+use crate::int::{overflow, IdInt};
+use crate::radix::format_id;
+use crate::rules::IdRule;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InvalidId<T: IdInt = usize> {
+    pub id: T,
+    /// the rule, or the part of a combined rule, that flagged the id
+    pub rule: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RangeReport<T: IdInt = usize> {
+    pub min: T,
+    pub max: T,
+    pub count: T,
+    pub sum: T,
+}
+
+/// The invalid ids in [min, max] with the rule that matched each, built lazily
+pub fn invalid_ids<'a, T: IdInt>(min: &T, max: &T, rule: &'a dyn IdRule<T>, radix: u32) -> impl Iterator<Item = InvalidId<T>> + 'a {
+    rule.invalid_ids(min, max, radix).map(move |id| InvalidId {
+        rule: rule.matching_rule(&id, radix).unwrap_or_else(|| rule.spec()),
+        id,
+    })
+}
+
+pub fn report_range<T: IdInt>(min: &T, max: &T, rule: &dyn IdRule<T>, radix: u32) -> anyhow::Result<RangeReport<T>> {
+    let range = format!("{}-{}", format_id(min, radix), format_id(max, radix));
+    Ok(RangeReport {
+        min: min.clone(),
+        max: max.clone(),
+        count: rule.count_invalid(min, max, radix).ok_or_else(|| overflow::<T>(&format!("invalid id count of {range}")))?,
+        sum: rule.sum_invalid(min, max, radix).ok_or_else(|| overflow::<T>(&format!("invalid id sum of {range}")))?,
+    })
+}
+
+/// One report per input range, in input order and without merging overlaps
+pub fn report_ranges<T: IdInt>(ranges: &[(T, T)], rule: &dyn IdRule<T>, radix: u32) -> anyhow::Result<Vec<RangeReport<T>>> {
+    ranges.iter().map(|(min, max)| report_range(min, max, rule, radix)).collect()
+}
+
+fn format_ids<T: IdInt>(report: &RangeReport<T>, rule: &dyn IdRule<T>, radix: u32) -> String {
+    invalid_ids(&report.min, &report.max, rule, radix)
+        .map(|invalid| format!("{} ({})", format_id(&invalid.id, radix), invalid.rule))
+        .collect::<Vec<String>>()
+        .join(", ")
+}
+
+pub fn write_table<T: IdInt>(out: &mut impl Write, reports: &[RangeReport<T>], rule: &dyn IdRule<T>, radix: u32, with_ids: bool) -> anyhow::Result<()> {
+    let ranges = reports.iter()
+        .map(|r| format!("{}-{}", format_id(&r.min, radix), format_id(&r.max, radix)))
+        .collect::<Vec<String>>();
+    let counts = reports.iter().map(|r| r.count.to_string()).collect::<Vec<String>>();
+    let sums = reports.iter().map(|r| format_id(&r.sum, radix)).collect::<Vec<String>>();
+
+    let range_width = ranges.iter().map(|r| r.len()).max().unwrap_or(0).max("range".len());
+    let count_width = counts.iter().map(|c| c.len()).max().unwrap_or(0).max("count".len());
+    let sum_width = sums.iter().map(|s| s.len()).max().unwrap_or(0).max("sum".len());
+
+    write!(out, "{:<range_width$}  {:>count_width$}  {:>sum_width$}", "range", "count", "sum")?;
+    if with_ids { write!(out, "  invalid ids")?; }
+    writeln!(out)?;
+
+    for (((report, range), count), sum) in reports.iter().zip(&ranges).zip(&counts).zip(&sums) {
+        write!(out, "{range:<range_width$}  {count:>count_width$}  {sum:>sum_width$}")?;
+        if with_ids { write!(out, "  {}", format_ids(report, rule, radix))?; }
+        writeln!(out)?;
+    }
+
+    Ok(())
+}
+
+pub fn write_csv<T: IdInt>(out: &mut impl Write, reports: &[RangeReport<T>], rule: &dyn IdRule<T>, radix: u32, with_ids: bool) -> anyhow::Result<()> {
+    let mut writer = csv::Writer::from_writer(out);
+
+    let mut header = vec!["min", "max", "count", "sum"];
+    if with_ids { header.push("invalid_ids"); }
+    writer.write_record(&header)?;
+
+    for report in reports {
+        let mut record = vec![
+            format_id(&report.min, radix),
+            format_id(&report.max, radix),
+            report.count.to_string(),
+            format_id(&report.sum, radix),
+        ];
+        if with_ids { record.push(format_ids(report, rule, radix)); }
+        writer.write_record(&record)?;
+    }
+    writer.flush()?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rules::{parse_rule, RepeatedAtLeast, RepeatedExactly};
+
+    const SAMPLE: [(usize, usize); 11] = [
+        (11, 22), (95, 115), (998, 1012), (1188511880, 1188511890), (222220, 222224),
+        (1698522, 1698528), (446443, 446449), (38593856, 38593862), (565653, 565659),
+        (824824821, 824824827), (2121212118, 2121212124),
+    ];
+
+    #[test]
+    fn test_sample_walkthrough_part1() {
+        let ids = SAMPLE.iter()
+            .map(|(min, max)| invalid_ids(min, max, &RepeatedExactly(2), 10).map(|i| i.id).collect::<Vec<_>>())
+            .collect::<Vec<_>>();
+
+        let answer: Vec<Vec<usize>> = vec![
+            vec![11, 22], vec![99], vec![1010], vec![1188511885], vec![222222],
+            vec![], vec![446446], vec![38593859], vec![], vec![], vec![],
+        ];
+        assert_eq!(answer, ids);
+    }
+
+    #[test]
+    fn test_sample_walkthrough_part2() -> anyhow::Result<()> {
+        let reports = report_ranges(&SAMPLE, &RepeatedAtLeast(2), 10)?;
+
+        let counts = reports.iter().map(|r| r.count).collect::<Vec<_>>();
+        assert_eq!(vec![2, 2, 2, 1, 1, 0, 1, 1, 1, 1, 1], counts);
+        assert_eq!(vec![99, 111], invalid_ids(&95usize, &115, &RepeatedAtLeast(2), 10).map(|i| i.id).collect::<Vec<_>>());
+        assert_eq!(4174379265, reports.iter().map(|r| r.sum).sum::<usize>());
+        Ok(())
+    }
+
+    #[test]
+    fn test_write_table() -> anyhow::Result<()> {
+        let rule = parse_rule::<usize>("or(exactly:2,palindrome)")?;
+        let reports = report_ranges(&[(95, 115), (998, 1012)], rule.as_ref(), 10)?;
+
+        let mut out = vec![];
+        write_table(&mut out, &reports, rule.as_ref(), 10, true)?;
+
+        let expected = "\
+range     count   sum  invalid ids
+95-115        3   311  99 (exactly:2), 101 (palindrome), 111 (palindrome)
+998-1012      3  3010  999 (palindrome), 1001 (palindrome), 1010 (exactly:2)
+";
+        assert_eq!(expected, String::from_utf8(out)?);
+
+        // a count wider than the header widens its column
+        let reports = report_ranges(&[(1usize, 999_999_999_999), (11, 22)], &RepeatedExactly(2), 10)?;
+        let mut out = vec![];
+        write_table(&mut out, &reports, &RepeatedExactly(2), 10, false)?;
+
+        let expected = "\
+range            count                 sum
+1-999999999999  999999  495495540949540950
+11-22                2                  33
+";
+        assert_eq!(expected, String::from_utf8(out)?);
+        Ok(())
+    }
+
+    #[test]
+    fn test_write_csv() -> anyhow::Result<()> {
+        let rule = RepeatedExactly(2);
+        let reports = report_ranges(&[(0x10usize, 0x22)], &rule, 16)?;
+
+        let mut out = vec![];
+        write_csv(&mut out, &reports, &rule, 16, true)?;
+
+        assert_eq!("min,max,count,sum,invalid_ids\n10,22,2,33,\"11 (exactly:2), 22 (exactly:2)\"\n", String::from_utf8(out)?);
+        Ok(())
+    }
+}
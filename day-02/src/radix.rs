@@ -0,0 +1,165 @@
+use anyhow::{bail, Context};
+use num_bigint::BigUint;
+use num_traits::Num;
+
+use crate::int::{from_u32, overflow, IdInt};
+
+pub const DIGITS: &[u8; 36] = b"0123456789abcdefghijklmnopqrstuvwxyz";
+
+pub fn check_radix(radix: u32) -> anyhow::Result<u32> {
+    if !(2..=36).contains(&radix) {
+        bail!("Radix must be between 2 and 36, got {radix}");
+    }
+    Ok(radix)
+}
+
+/// Digits of `id` in `radix`, most significant first
+pub fn digits<T: IdInt>(id: &T, radix: u32) -> Vec<u8> {
+    let radix = from_u32::<T>(radix);
+    let mut id = id.clone();
+    let mut digits = vec![];
+
+    loop {
+        let (rest, digit) = id.div_rem(&radix);
+        digits.push(digit.to_u8().expect("a digit is smaller than the radix"));
+        id = rest;
+        if id.is_zero() { break }
+    }
+    digits.reverse();
+
+    digits
+}
+
+pub fn format_id<T: IdInt>(id: &T, radix: u32) -> String {
+    digits(id, radix).iter().map(|d| DIGITS[*d as usize] as char).collect()
+}
+
+pub fn parse_id<T: IdInt>(text: &str, radix: u32) -> anyhow::Result<T> {
+    if let Ok(id) = T::from_str_radix(text, radix) {
+        return Ok(id)
+    }
+    // tell a bad digit apart from an id that's just too big for T
+    BigUint::from_str_radix(text, radix).with_context(|| format!("Invalid base {radix} id {text:?}"))?;
+    Err(overflow::<T>(&format!("id {text}")))
+}
+
+/// Parses "min-max" ranges with the ids written in `radix`. Ranges are separated by commas
+/// or line breaks, whitespace and blank lines are ignored and `#` starts a comment.
+pub fn parse_ranges<T: IdInt>(data: &str, radix: u32) -> anyhow::Result<Vec<(T, T)>> {
+    let mut ranges = vec![];
+
+    for (i, line) in data.lines().enumerate() {
+        let line = line.split_once('#').map_or(line, |(ranges, _)| ranges);
+
+        for range in line.split(',').map(str::trim).filter(|r| !r.is_empty()) {
+            let parse = || -> anyhow::Result<(T, T)> {
+                let (min, max) = range.split_once('-').context("expected min-max")?;
+                let (min, max) = (parse_id::<T>(min.trim(), radix)?, parse_id::<T>(max.trim(), radix)?);
+                if max < min {
+                    bail!("it's reversed, {} is smaller than {}", format_id(&max, radix), format_id(&min, radix))
+                }
+                Ok((min, max))
+            };
+            ranges.push(parse().with_context(|| format!("Invalid range {range:?} on line {}", i + 1))?);
+        }
+    }
+
+    Ok(ranges)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::int::Overflow;
+    use proptest::prelude::*;
+
+    #[test]
+    fn test_digits() {
+        assert_eq!(vec![0], digits(&0usize, 10));
+        assert_eq!(vec![1, 2, 3], digits(&123usize, 10));
+        assert_eq!(vec![1, 0, 1, 0], digits(&10u64, 2));
+        assert_eq!(vec![15, 15], digits(&255u128, 16));
+        assert_eq!(vec![1; 30], digits(&"1".repeat(30).parse::<BigUint>().unwrap(), 10));
+    }
+
+    #[test]
+    fn test_format_and_parse() -> anyhow::Result<()> {
+        assert_eq!("ff", format_id(&255usize, 16));
+        assert_eq!("zz", format_id(&(35usize * 36 + 35), 36));
+        assert_eq!("1010", format_id(&10usize, 2));
+
+        assert_eq!(255usize, parse_id("FF", 16)?);
+        assert_eq!(255usize, parse_id("ff", 16)?);
+        assert!(parse_id::<usize>("12", 2).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_too_big() -> anyhow::Result<()> {
+        let id = "123456789012345678901234567890";
+
+        let err = parse_id::<u64>(id, 10).unwrap_err();
+        assert_eq!(Some(&Overflow { width: "u64", what: format!("id {id}") }), err.downcast_ref::<Overflow>());
+        assert_eq!(format!("id {id} doesn't fit in u64"), err.to_string());
+
+        assert_eq!(id, parse_id::<u128>(id, 10)?.to_string());
+        assert_eq!(id, parse_id::<BigUint>(id, 10)?.to_string());
+        assert!(parse_id::<u64>("12x", 10).unwrap_err().downcast_ref::<Overflow>().is_none());
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_ranges_layout() -> anyhow::Result<()> {
+        let text = "\
+# sample ranges
+11-22, 95-115 ,
+998 - 1012,1188511880-1188511890   # trailing comment
+
+  222220-222224
+,446443-446449,
+";
+        let expected = vec![(11usize, 22), (95, 115), (998, 1012), (1188511880, 1188511890), (222220, 222224), (446443, 446449)];
+        assert_eq!(expected, parse_ranges(text, 10)?);
+        assert_eq!(vec![(11usize, 22)], parse_ranges("11-22\r\n", 10)?);
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_ranges_errors() {
+        let err = parse_ranges::<usize>("11-22\n95-115,30-20", 10).unwrap_err();
+        assert_eq!("Invalid range \"30-20\" on line 2: it's reversed, 20 is smaller than 30", format!("{err:#}"));
+
+        let err = parse_ranges::<usize>("11-22\n\n# 1-2\n5", 10).unwrap_err();
+        assert_eq!("Invalid range \"5\" on line 4: expected min-max", format!("{err:#}"));
+
+        // written back in the base the range was in
+        let err = parse_ranges::<usize>("ff-1a", 16).unwrap_err();
+        assert_eq!("Invalid range \"ff-1a\" on line 1: it's reversed, 1a is smaller than ff", format!("{err:#}"));
+
+        let err = parse_ranges::<usize>("1 2-3", 10).unwrap_err();
+        assert!(format!("{err:#}").starts_with("Invalid range \"1 2-3\" on line 1: Invalid base 10 id"));
+    }
+
+    #[test]
+    fn test_parse_ranges() -> anyhow::Result<()> {
+        assert_eq!(vec![(0xausize, 0x1f), (0xff, 0x100)], parse_ranges("a-1f,ff-100", 16)?);
+        assert_eq!(vec![(2usize, 5)], parse_ranges("10-101", 2)?);
+        assert!(parse_ranges::<usize>("10", 10).is_err());
+        assert!(parse_ranges::<usize>("", 10)?.is_empty());
+        assert!(check_radix(37).is_err());
+        assert!(check_radix(1).is_err());
+        Ok(())
+    }
+
+    proptest! {
+        #[test]
+        fn prop_format_parse_round_trip(ranges in prop::collection::vec((any::<u64>(), any::<u64>()), 0..10), radix in 2u32..=36) {
+            let ranges = ranges.into_iter().map(|(a, b)| (a.min(b), a.max(b))).collect::<Vec<_>>();
+            let text = ranges.iter().map(|(min, max)| format!("{}-{}", format_id(min, radix), format_id(max, radix))).collect::<Vec<_>>().join(",");
+
+            prop_assert_eq!(&ranges, &parse_ranges::<u64>(&text, radix).unwrap());
+            let big = parse_ranges::<BigUint>(&text, radix).unwrap();
+            prop_assert!(big.iter().zip(&ranges).all(|((a, b), (min, max))| *a == BigUint::from(*min) && *b == BigUint::from(*max)));
+        }
+    }
+}
@@ -0,0 +1,258 @@
+use crate::int::IdInt;
+
+/// A set of ids stored as sorted, disjoint and non-adjacent inclusive ranges
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct IdRangeSet<T: IdInt = usize> {
+    ranges: Vec<(T, T)>,
+}
+
+impl<T: IdInt> Default for IdRangeSet<T> {
+    fn default() -> Self {
+        IdRangeSet::new()
+    }
+}
+
+impl<T: IdInt> IdRangeSet<T> {
+    pub fn new() -> Self {
+        IdRangeSet { ranges: vec![] }
+    }
+
+    /// Builds a set from ranges in any order, merging the ones that overlap or touch.
+    /// Reversed ranges (min > max) are empty and get dropped.
+    pub fn from_ranges(ranges: impl IntoIterator<Item = (T, T)>) -> Self {
+        let mut ranges = ranges.into_iter().filter(|(min, max)| min <= max).collect::<Vec<_>>();
+        ranges.sort_unstable();
+
+        let mut merged: Vec<(T, T)> = Vec::with_capacity(ranges.len());
+        for (min, max) in ranges {
+            match merged.last_mut() {
+                Some((_, last_max)) if min <= *last_max || last_max.checked_add(&T::one()).as_ref() == Some(&min) => {
+                    if max > *last_max {
+                        *last_max = max;
+                    }
+                }
+                _ => merged.push((min, max)),
+            }
+        }
+
+        IdRangeSet { ranges: merged }
+    }
+
+    pub fn insert(&mut self, min: T, max: T) {
+        let ranges = std::mem::take(&mut self.ranges);
+        *self = IdRangeSet::from_ranges(ranges.into_iter().chain([(min, max)]));
+    }
+
+    pub fn ranges(&self) -> &[(T, T)] {
+        &self.ranges
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &(T, T)> {
+        self.ranges.iter()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.ranges.is_empty()
+    }
+
+    /// Number of ids in the set, None if it doesn't fit in T
+    pub fn total_len(&self) -> Option<T> {
+        self.ranges.iter().try_fold(T::zero(), |acc, (min, max)| {
+            acc.checked_add(&(max.clone() - min.clone()))?.checked_add(&T::one())
+        })
+    }
+
+    pub fn contains(&self, id: &T) -> bool {
+        self.contains_range(id, id)
+    }
+
+    /// True if every id in [min, max] is in the set
+    pub fn contains_range(&self, min: &T, max: &T) -> bool {
+        // the only candidate is the last range starting at or before min
+        let index = self.ranges.partition_point(|(start, _)| start <= min);
+        index > 0 && self.ranges[index - 1].1 >= *max
+    }
+
+    /// The ranges cut into pieces of at most `max_len` ids, in order
+    pub fn split(&self, max_len: &T) -> Vec<(T, T)> {
+        let step = if max_len.is_zero() { T::zero() } else { max_len.clone() - T::one() };
+        let mut pieces = vec![];
+
+        for (min, max) in &self.ranges {
+            let mut start = min.clone();
+            loop {
+                let end = match start.checked_add(&step) {
+                    Some(end) if end < *max => end,
+                    _ => max.clone(),
+                };
+                pieces.push((start, end.clone()));
+                if end == *max { break }
+                start = end + T::one();
+            }
+        }
+
+        pieces
+    }
+
+    pub fn union(&self, other: &IdRangeSet<T>) -> IdRangeSet<T> {
+        IdRangeSet::from_ranges(self.ranges.iter().chain(other.ranges.iter()).cloned())
+    }
+
+    pub fn intersection(&self, other: &IdRangeSet<T>) -> IdRangeSet<T> {
+        let mut ranges = vec![];
+        let (mut i, mut j) = (0, 0);
+
+        while i < self.ranges.len() && j < other.ranges.len() {
+            let (a_min, a_max) = &self.ranges[i];
+            let (b_min, b_max) = &other.ranges[j];
+
+            let min = a_min.max(b_min);
+            let max = a_max.min(b_max);
+            if min <= max {
+                ranges.push((min.clone(), max.clone()));
+            }
+
+            // drop whichever range ends first, the other may still overlap the next one
+            if a_max < b_max { i += 1 } else { j += 1 }
+        }
+
+        IdRangeSet { ranges }
+    }
+
+    pub fn difference(&self, other: &IdRangeSet<T>) -> IdRangeSet<T> {
+        let mut ranges = vec![];
+        let mut j = 0;
+
+        for (min, max) in &self.ranges {
+            let mut start = min.clone();
+
+            // skip the ranges of other that end before this one
+            while j < other.ranges.len() && other.ranges[j].1 < *min {
+                j += 1;
+            }
+
+            let mut k = j;
+            while k < other.ranges.len() && other.ranges[k].0 <= *max {
+                let (cut_min, cut_max) = &other.ranges[k];
+                if *cut_min > start {
+                    ranges.push((start.clone(), cut_min.clone() - T::one()));
+                }
+                if cut_max >= max {
+                    start = max.clone();
+                    break
+                }
+                start = cut_max.clone() + T::one();
+                k += 1;
+            }
+
+            if k == other.ranges.len() || other.ranges[k].0 > *max {
+                ranges.push((start, max.clone()));
+            }
+        }
+
+        IdRangeSet { ranges }
+    }
+}
+
+impl<T: IdInt> From<Vec<(T, T)>> for IdRangeSet<T> {
+    fn from(ranges: Vec<(T, T)>) -> Self {
+        IdRangeSet::from_ranges(ranges)
+    }
+}
+
+impl<T: IdInt> FromIterator<(T, T)> for IdRangeSet<T> {
+    fn from_iter<I: IntoIterator<Item = (T, T)>>(iter: I) -> Self {
+        IdRangeSet::from_ranges(iter)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use num_bigint::BigUint;
+
+    #[test]
+    fn test_normalize() {
+        let set: IdRangeSet = IdRangeSet::from(vec![(10, 20), (1, 3), (15, 25), (4, 5), (30, 30), (9, 8)]);
+        assert_eq!(set.ranges(), &[(1, 5), (10, 25), (30, 30)]);
+        assert_eq!(Some(22), set.total_len());
+    }
+
+    #[test]
+    fn test_contains() {
+        let set: IdRangeSet = IdRangeSet::from(vec![(1, 5), (10, 25)]);
+
+        assert!(set.contains(&1));
+        assert!(set.contains(&5));
+        assert!(!set.contains(&6));
+        assert!(!set.contains(&0));
+        assert!(set.contains(&25));
+        assert!(!set.contains(&26));
+
+        assert!(set.contains_range(&10, &25));
+        assert!(set.contains_range(&12, &13));
+        assert!(!set.contains_range(&4, &11));
+    }
+
+    #[test]
+    fn test_big_ids() {
+        let big = |s: &str| s.parse::<BigUint>().unwrap();
+        let a = IdRangeSet::from(vec![(big("100000000000000000000"), big("100000000000000000009"))]);
+        let b = IdRangeSet::from(vec![(big("100000000000000000005"), big("200000000000000000000"))]);
+
+        assert_eq!(a.difference(&b).ranges(), &[(big("100000000000000000000"), big("100000000000000000004"))]);
+        assert_eq!(Some(big("100000000000000000001")), a.union(&b).total_len());
+    }
+
+    #[test]
+    fn test_split() {
+        let set: IdRangeSet = IdRangeSet::from(vec![(1, 10), (20, 21), (30, 30)]);
+
+        assert_eq!(set.split(&4), vec![(1, 4), (5, 8), (9, 10), (20, 21), (30, 30)]);
+        assert_eq!(set.split(&100), set.ranges().to_vec());
+        let set = IdRangeSet::from(vec![(u64::MAX - 2, u64::MAX)]);
+        assert_eq!(set.split(&2), vec![(u64::MAX - 2, u64::MAX - 1), (u64::MAX, u64::MAX)]);
+        assert_eq!(None, IdRangeSet::from(vec![(0, u64::MAX)]).total_len());
+    }
+
+    #[test]
+    fn test_union() {
+        let a: IdRangeSet = IdRangeSet::from(vec![(1, 5), (20, 30)]);
+        let b: IdRangeSet = IdRangeSet::from(vec![(6, 8), (25, 40)]);
+
+        assert_eq!(a.union(&b).ranges(), &[(1, 8), (20, 40)]);
+    }
+
+    #[test]
+    fn test_intersection() {
+        let a: IdRangeSet = IdRangeSet::from(vec![(1, 10), (20, 30)]);
+        let b: IdRangeSet = IdRangeSet::from(vec![(5, 22), (25, 26), (29, 40)]);
+
+        assert_eq!(a.intersection(&b).ranges(), &[(5, 10), (20, 22), (25, 26), (29, 30)]);
+        assert!(a.intersection(&IdRangeSet::new()).is_empty());
+    }
+
+    #[test]
+    fn test_difference() {
+        let a: IdRangeSet = IdRangeSet::from(vec![(1, 10), (20, 30)]);
+        let b: IdRangeSet = IdRangeSet::from(vec![(3, 4), (8, 22), (25, 26)]);
+
+        assert_eq!(a.difference(&b).ranges(), &[(1, 2), (5, 7), (23, 24), (27, 30)]);
+        assert_eq!(b.difference(&a).ranges(), &[(11, 19)]);
+        assert_eq!(a.difference(&IdRangeSet::new()), a);
+        assert!(a.difference(&a).is_empty());
+    }
+
+    #[test]
+    fn test_difference_matches_contains() {
+        let a: IdRangeSet = IdRangeSet::from(vec![(0, 12), (15, 40), (50, 60)]);
+        let b: IdRangeSet = IdRangeSet::from(vec![(0, 0), (5, 16), (18, 18), (40, 55), (60, 70)]);
+        let diff = a.difference(&b);
+        let both = a.intersection(&b);
+
+        for id in 0..80 {
+            assert_eq!(a.contains(&id) && !b.contains(&id), diff.contains(&id), "{id}");
+            assert_eq!(a.contains(&id) && b.contains(&id), both.contains(&id), "{id}");
+        }
+    }
+}
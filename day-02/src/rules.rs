@@ -0,0 +1,470 @@
+use std::fmt::Debug;
+use std::str::FromStr;
+
+use anyhow::{anyhow, bail, Context};
+
+use crate::int::{id_range, overflow, series_sum, IdInt};
+use crate::invalid::{
+    count_repeated_at_least, count_repeated_exactly, ids_repeated_at_least, ids_repeated_exactly,
+    is_repeated_exactly, n_digits, smallest_repeating_block, sum_repeated_at_least, sum_repeated_exactly,
+};
+use crate::radix::digits;
+use crate::ranges::IdRangeSet;
+
+/// Decides which ids are invalid (suspicious), looking at their digits in `radix`.
+/// Counts and sums are None when they don't fit in T.
+pub trait IdRule<T: IdInt = usize>: Debug + Send + Sync {
+    fn is_invalid(&self, id: &T, radix: u32) -> bool;
+
+    /// The rule written the way `parse_rule` reads it
+    fn spec(&self) -> String;
+
+    /// The rule that flags `id`, for combined rules the part of it that matched
+    fn matching_rule(&self, id: &T, radix: u32) -> Option<String> {
+        self.is_invalid(id, radix).then(|| self.spec())
+    }
+
+    /// The invalid ids in [min, max], lazily and in increasing order. Rules that can
+    /// build their invalid ids directly should override this, the default checks every id.
+    fn invalid_ids<'a>(&'a self, min: &T, max: &T, radix: u32) -> Box<dyn Iterator<Item = T> + 'a> {
+        Box::new(id_range(min.clone(), max.clone()).filter(move |id| self.is_invalid(id, radix)))
+    }
+
+    fn count_invalid(&self, min: &T, max: &T, radix: u32) -> Option<T> {
+        self.invalid_ids(min, max, radix).try_fold(T::zero(), |count, _| count.checked_add(&T::one()))
+    }
+
+    fn sum_invalid(&self, min: &T, max: &T, radix: u32) -> Option<T> {
+        self.invalid_ids(min, max, radix).try_fold(T::zero(), |sum, id| sum.checked_add(&id))
+    }
+
+    /// True if `count_invalid` and `sum_invalid` work the answer out without visiting every
+    /// id, so a range takes the same time however wide it is
+    fn is_arithmetic(&self) -> bool {
+        false
+    }
+}
+
+/// The digits are one block repeated exactly `times` times, e.g. 123123 for 2
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RepeatedExactly(pub u32);
+
+/// The digits are one block repeated `times` times or more, e.g. 121212 for 2
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RepeatedAtLeast(pub u32);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Palindrome;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DigitSumDivisibleBy(pub usize);
+
+#[derive(Debug)]
+pub struct And<T: IdInt = usize>(pub Box<dyn IdRule<T>>, pub Box<dyn IdRule<T>>);
+
+#[derive(Debug)]
+pub struct Or<T: IdInt = usize>(pub Box<dyn IdRule<T>>, pub Box<dyn IdRule<T>>);
+
+#[derive(Debug)]
+pub struct Not<T: IdInt = usize>(pub Box<dyn IdRule<T>>);
+
+impl<T: IdInt> IdRule<T> for RepeatedExactly {
+    fn is_arithmetic(&self) -> bool {
+        true
+    }
+
+    fn is_invalid(&self, id: &T, radix: u32) -> bool {
+        is_repeated_exactly(id, self.0, radix)
+    }
+
+    fn spec(&self) -> String {
+        format!("exactly:{}", self.0)
+    }
+
+    fn invalid_ids<'a>(&'a self, min: &T, max: &T, radix: u32) -> Box<dyn Iterator<Item = T> + 'a> {
+        if self.0 == 0 {
+            return Box::new(std::iter::empty())
+        }
+        Box::new(ids_repeated_exactly(min, max, self.0, radix))
+    }
+
+    fn count_invalid(&self, min: &T, max: &T, radix: u32) -> Option<T> {
+        if self.0 == 0 {
+            return Some(T::zero())
+        }
+        count_repeated_exactly(min, max, self.0, radix)
+    }
+
+    fn sum_invalid(&self, min: &T, max: &T, radix: u32) -> Option<T> {
+        if self.0 == 0 {
+            return Some(T::zero())
+        }
+        sum_repeated_exactly(min, max, self.0, radix)
+    }
+}
+
+impl<T: IdInt> IdRule<T> for RepeatedAtLeast {
+    fn is_arithmetic(&self) -> bool {
+        true
+    }
+
+    fn is_invalid(&self, id: &T, radix: u32) -> bool {
+        if self.0 <= 1 {
+            return true
+        }
+        // the shortest block gives the most repetitions
+        match smallest_repeating_block(id, radix) {
+            Some((_, block_len)) => n_digits(id, radix) / block_len >= self.0,
+            None => false,
+        }
+    }
+
+    fn spec(&self) -> String {
+        format!("at-least:{}", self.0)
+    }
+
+    fn invalid_ids<'a>(&'a self, min: &T, max: &T, radix: u32) -> Box<dyn Iterator<Item = T> + 'a> {
+        Box::new(ids_repeated_at_least(min, max, self.0.max(1), radix))
+    }
+
+    fn count_invalid(&self, min: &T, max: &T, radix: u32) -> Option<T> {
+        count_repeated_at_least(min, max, self.0.max(1), radix)
+    }
+
+    fn sum_invalid(&self, min: &T, max: &T, radix: u32) -> Option<T> {
+        sum_repeated_at_least(min, max, self.0.max(1), radix)
+    }
+}
+
+impl<T: IdInt> IdRule<T> for Palindrome {
+    fn is_invalid(&self, id: &T, radix: u32) -> bool {
+        let digits = digits(id, radix);
+        digits.iter().eq(digits.iter().rev())
+    }
+
+    fn spec(&self) -> String {
+        "palindrome".to_string()
+    }
+}
+
+impl<T: IdInt> IdRule<T> for DigitSumDivisibleBy {
+    fn is_invalid(&self, id: &T, radix: u32) -> bool {
+        let sum = digits(id, radix).iter().map(|d| *d as usize).sum::<usize>();
+        self.0 != 0 && sum.is_multiple_of(self.0)
+    }
+
+    fn spec(&self) -> String {
+        format!("digit-sum:{}", self.0)
+    }
+}
+
+impl<T: IdInt> IdRule<T> for And<T> {
+    fn is_invalid(&self, id: &T, radix: u32) -> bool {
+        self.0.is_invalid(id, radix) && self.1.is_invalid(id, radix)
+    }
+
+    fn spec(&self) -> String {
+        format!("and({},{})", self.0.spec(), self.1.spec())
+    }
+}
+
+impl<T: IdInt> IdRule<T> for Or<T> {
+    fn is_invalid(&self, id: &T, radix: u32) -> bool {
+        self.0.is_invalid(id, radix) || self.1.is_invalid(id, radix)
+    }
+
+    fn spec(&self) -> String {
+        format!("or({},{})", self.0.spec(), self.1.spec())
+    }
+
+    fn matching_rule(&self, id: &T, radix: u32) -> Option<String> {
+        self.0.matching_rule(id, radix).or_else(|| self.1.matching_rule(id, radix))
+    }
+}
+
+impl<T: IdInt> IdRule<T> for Not<T> {
+    fn is_arithmetic(&self) -> bool {
+        self.0.is_arithmetic()
+    }
+
+    fn is_invalid(&self, id: &T, radix: u32) -> bool {
+        !self.0.is_invalid(id, radix)
+    }
+
+    fn spec(&self) -> String {
+        format!("not({})", self.0.spec())
+    }
+
+    fn count_invalid(&self, min: &T, max: &T, radix: u32) -> Option<T> {
+        let n_ids = (max.clone() - min.clone()).checked_add(&T::one())?;
+        Some(n_ids - self.0.count_invalid(min, max, radix)?)
+    }
+
+    fn sum_invalid(&self, min: &T, max: &T, radix: u32) -> Option<T> {
+        // everything in the range minus what the inner rule flags
+        Some(series_sum(min, max)? - self.0.sum_invalid(min, max, radix)?)
+    }
+}
+
+pub fn sum_invalid_ids<T: IdInt>(ranges: &IdRangeSet<T>, rule: &dyn IdRule<T>, radix: u32) -> anyhow::Result<T> {
+    ranges.iter()
+        .try_fold(T::zero(), |sum, (min, max)| sum.checked_add(&rule.sum_invalid(min, max, radix)?))
+        .ok_or_else(|| overflow::<T>("sum of the invalid ids"))
+}
+
+/*
+Rules are written as
+    exactly:2  at-least:2  palindrome  digit-sum:7
+and combined with
+    and(rule,rule)  or(rule,rule)  not(rule)
+ */
+pub fn parse_rule<T: IdInt>(spec: &str) -> anyhow::Result<Box<dyn IdRule<T>>> {
+    let spec = spec.trim();
+
+    if let Some(args) = spec.strip_prefix("and(").and_then(|s| s.strip_suffix(')')) {
+        let (a, b) = split_args(args)?;
+        return Ok(Box::new(And(parse_rule(a)?, parse_rule(b)?)))
+    }
+    if let Some(args) = spec.strip_prefix("or(").and_then(|s| s.strip_suffix(')')) {
+        let (a, b) = split_args(args)?;
+        return Ok(Box::new(Or(parse_rule(a)?, parse_rule(b)?)))
+    }
+    if let Some(arg) = spec.strip_prefix("not(").and_then(|s| s.strip_suffix(')')) {
+        return Ok(Box::new(Not(parse_rule(arg)?)))
+    }
+
+    let (name, value) = match spec.split_once(':') {
+        Some((name, value)) => (name, Some(value)),
+        None => (spec, None),
+    };
+
+    let rule: Box<dyn IdRule<T>> = match name {
+        "exactly" => Box::new(RepeatedExactly(rule_number(name, value)?)),
+        "at-least" => Box::new(RepeatedAtLeast(rule_number(name, value)?)),
+        "palindrome" => Box::new(Palindrome),
+        "digit-sum" => Box::new(DigitSumDivisibleBy(rule_number(name, value)?)),
+        _ => bail!("Unknown rule {spec:?}"),
+    };
+
+    Ok(rule)
+}
+
+// the number after the colon, in whatever type the rule keeps it so it can't be cut short
+fn rule_number<N: FromStr>(name: &str, value: Option<&str>) -> anyhow::Result<N>
+where
+    N::Err: std::error::Error + Send + Sync + 'static,
+{
+    let value = value.ok_or_else(|| anyhow!("Rule {name:?} needs a number, e.g. {name}:2"))?;
+    value.parse().with_context(|| format!("Invalid number {value:?} for rule {name:?}"))
+}
+
+// splits "a,b" on the comma that isn't nested inside parentheses
+fn split_args(args: &str) -> anyhow::Result<(&str, &str)> {
+    let mut depth = 0;
+
+    for (i, ch) in args.char_indices() {
+        match ch {
+            '(' => depth += 1,
+            ')' => depth -= 1,
+            ',' if depth == 0 => return Ok((&args[..i], &args[i + 1..])),
+            _ => {}
+        }
+    }
+
+    bail!("Expected two rules separated by a comma in {args:?}")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{part1, part2};
+    use num_bigint::BigUint;
+    use proptest::prelude::*;
+
+    fn is_invalid(rule: &dyn IdRule, id: usize, radix: u32) -> bool {
+        rule.is_invalid(&id, radix)
+    }
+
+    #[test]
+    fn test_repeated_rules_match_parts() {
+        for id in 1..20_000 {
+            assert_eq!(!part1::is_valid_id(id), is_invalid(&RepeatedExactly(2), id, 10), "{id}");
+            assert_eq!(!part2::is_valid_id(id), is_invalid(&RepeatedAtLeast(2), id, 10), "{id}");
+        }
+    }
+
+    #[test]
+    fn test_fast_path_matches_scan() {
+        let rules: Vec<Box<dyn IdRule>> = vec![
+            Box::new(RepeatedExactly(2)),
+            Box::new(RepeatedExactly(3)),
+            Box::new(RepeatedAtLeast(2)),
+            Box::new(RepeatedAtLeast(3)),
+            Box::new(Not(Box::new(RepeatedAtLeast(2)))),
+        ];
+
+        for rule in rules {
+            for (min, max) in [(1, 1000), (95, 115), (9_000, 130_000)] {
+                let scanned = (min..=max).filter(|id| rule.is_invalid(id, 10)).sum::<usize>();
+                assert_eq!(Some(scanned), rule.sum_invalid(&min, &max, 10), "{rule:?} {min}-{max}");
+            }
+        }
+    }
+
+    #[test]
+    fn test_palindrome_and_digit_sum() {
+        assert!(is_invalid(&Palindrome, 7, 10));
+        assert!(is_invalid(&Palindrome, 12321, 10));
+        assert!(!is_invalid(&Palindrome, 12320, 10));
+
+        assert!(is_invalid(&DigitSumDivisibleBy(3), 111, 10));
+        assert!(!is_invalid(&DigitSumDivisibleBy(3), 112, 10));
+        assert!(!is_invalid(&DigitSumDivisibleBy(0), 0, 10));
+    }
+
+    #[test]
+    fn test_radix() -> anyhow::Result<()> {
+        assert!(is_invalid(&RepeatedExactly(2), 0b1010, 2));
+        assert!(!is_invalid(&RepeatedExactly(2), 0b1010, 10));
+        assert!(is_invalid(&RepeatedAtLeast(3), 0xababab, 16));
+        assert!(is_invalid(&Palindrome, 0xabba, 16));
+        assert!(is_invalid(&DigitSumDivisibleBy(4), 0b1111, 2));
+
+        let ranges: IdRangeSet = IdRangeSet::from(vec![(0x1200, 0x1300), (0xaa, 0xab)]);
+        assert_eq!(0x1212 + 0xaa, sum_invalid_ids(&ranges, &RepeatedExactly(2), 16)?);
+        let rule: Or = Or(Box::new(RepeatedExactly(2)), Box::new(Palindrome));
+        assert_eq!(0x1212 + 0x1221 + 0xaa, sum_invalid_ids(&ranges, &rule, 16)?);
+        Ok(())
+    }
+
+    #[test]
+    fn test_ids_and_counts_match_scan() {
+        let rules: Vec<Box<dyn IdRule>> = vec![
+            Box::new(RepeatedExactly(2)),
+            Box::new(RepeatedAtLeast(3)),
+            Box::new(Palindrome),
+            Box::new(Not(Box::new(RepeatedAtLeast(2)))),
+        ];
+
+        for rule in rules {
+            let scanned = (900..=1300).filter(|id| rule.is_invalid(id, 10)).collect::<Vec<_>>();
+            assert_eq!(scanned, rule.invalid_ids(&900, &1300, 10).collect::<Vec<_>>(), "{rule:?}");
+            assert_eq!(Some(scanned.len()), rule.count_invalid(&900, &1300, 10), "{rule:?}");
+        }
+    }
+
+    #[test]
+    fn test_spec_round_trip() -> anyhow::Result<()> {
+        for spec in ["exactly:2", "at-least:3", "palindrome", "digit-sum:7", "and(exactly:2,not(or(palindrome,digit-sum:3)))"] {
+            assert_eq!(spec, parse_rule::<usize>(spec)?.spec());
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_matching_rule() -> anyhow::Result<()> {
+        let rule = parse_rule::<usize>("or(exactly:2,palindrome)")?;
+
+        assert_eq!(Some("exactly:2".to_string()), rule.matching_rule(&1212, 10));
+        assert_eq!(Some("palindrome".to_string()), rule.matching_rule(&121, 10));
+        assert_eq!(None, rule.matching_rule(&123, 10));
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_rule() -> anyhow::Result<()> {
+        let rule = parse_rule::<usize>("and(exactly:2, not(palindrome))")?;
+        assert!(rule.is_invalid(&1212, 10));
+        assert!(!rule.is_invalid(&1111, 10));
+        assert!(!rule.is_invalid(&1213, 10));
+
+        let rule = parse_rule::<usize>("or(palindrome,digit-sum:5)")?;
+        assert!(rule.is_invalid(&121, 10));
+        assert!(rule.is_invalid(&23, 10));
+        assert!(!rule.is_invalid(&24, 10));
+
+        assert!(parse_rule::<usize>("exactly").is_err());
+        assert!(parse_rule::<usize>("exactly:x").is_err());
+        // too big for the u32 the rule keeps, rather than wrapping around to exactly:2
+        let err = parse_rule::<usize>("exactly:4294967298").unwrap_err();
+        assert_eq!("Invalid number \"4294967298\" for rule \"exactly\"", err.to_string());
+        assert!(parse_rule::<usize>("and(palindrome)").is_err());
+        assert!(parse_rule::<usize>("sorted").is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_sum_invalid_ids() -> anyhow::Result<()> {
+        let ranges: IdRangeSet = IdRangeSet::from(vec![(11, 22), (95, 115), (998, 1012)]);
+
+        assert_eq!(11 + 22 + 99 + 1010, sum_invalid_ids(&ranges, &RepeatedExactly(2), 10)?);
+        assert_eq!(11 + 22 + 99 + 111 + 999 + 1010, sum_invalid_ids(&ranges, &RepeatedAtLeast(2), 10)?);
+        Ok(())
+    }
+
+    #[test]
+    fn test_sum_overflow() -> anyhow::Result<()> {
+        // every 38 digit id fits in u128 but the sum of the ones repeated twice doesn't
+        let (min, max) = (10u128.pow(37), 10u128.pow(38) - 1);
+        let ranges = IdRangeSet::from(vec![(min, max)]);
+        let rule = parse_rule::<u128>("exactly:2")?;
+        let err = sum_invalid_ids(&ranges, rule.as_ref(), 10).unwrap_err();
+        assert_eq!("sum of the invalid ids doesn't fit in u128", err.to_string());
+
+        let ranges = IdRangeSet::from(vec![(BigUint::from(min), BigUint::from(max))]);
+        let sum = sum_invalid_ids(&ranges, parse_rule::<BigUint>("exactly:2")?.as_ref(), 10)?;
+        // blocks 10^18..10^19 - 1 times the multiplier 10^19 + 1
+        let blocks = (10u128.pow(18) + 10u128.pow(19) - 1) * (9 * 10u128.pow(18)) / 2;
+        assert_eq!(BigUint::from(blocks) * BigUint::from(10u128.pow(19) + 1), sum);
+        Ok(())
+    }
+
+    fn repeated(at_least: bool, times: u32) -> Box<dyn IdRule<u128>> {
+        match at_least {
+            true => Box::new(RepeatedAtLeast(times)),
+            false => Box::new(RepeatedExactly(times)),
+        }
+    }
+
+    fn ranges() -> impl Strategy<Value = IdRangeSet<u128>> {
+        prop::collection::vec((1u128..1_000_000, 0u128..5000), 0..8)
+            .prop_map(|ranges| IdRangeSet::from_ranges(ranges.into_iter().map(|(min, len)| (min, min + len))))
+    }
+
+    proptest! {
+        #[test]
+        fn prop_additive_over_split(min in 1u128..10u128.pow(15), len in 1u128..10u128.pow(9), cut in 0.0..1.0f64,
+                                    at_least in any::<bool>(), times in 1u32..5, radix in 2u32..=16) {
+            // [min, max] is [min, mid] and [mid + 1, max] side by side
+            let (max, mid) = (min + len, min + (len as f64 * cut) as u128);
+            let rule = repeated(at_least, times);
+
+            let whole = rule.sum_invalid(&min, &max, radix).unwrap();
+            let halves = rule.sum_invalid(&min, &mid, radix).unwrap() + rule.sum_invalid(&(mid + 1), &max, radix).unwrap();
+            prop_assert_eq!(whole, halves);
+            let whole = rule.count_invalid(&min, &max, radix).unwrap();
+            let halves = rule.count_invalid(&min, &mid, radix).unwrap() + rule.count_invalid(&(mid + 1), &max, radix).unwrap();
+            prop_assert_eq!(whole, halves);
+        }
+
+        #[test]
+        fn prop_fast_path_matches_scan(min in 1u128..10_000_000, len in 0u128..3000, at_least in any::<bool>(), times in 1u32..5, radix in 2u32..=16) {
+            let rule = repeated(at_least, times);
+            let scanned = (min..=min + len).filter(|id| rule.is_invalid(id, radix)).collect::<Vec<_>>();
+
+            prop_assert_eq!(Some(scanned.iter().sum()), rule.sum_invalid(&min, &(min + len), radix));
+            prop_assert_eq!(scanned, rule.invalid_ids(&min, &(min + len), radix).collect::<Vec<_>>());
+        }
+
+        #[test]
+        fn prop_additive_over_sets(a in ranges(), b in ranges(), at_least in any::<bool>(), times in 2u32..4) {
+            // the ids in both sets get counted twice on either side
+            let rule = repeated(at_least, times);
+            let sum = |set: &IdRangeSet<u128>| sum_invalid_ids(set, rule.as_ref(), 10).unwrap();
+
+            prop_assert_eq!(sum(&a) + sum(&b), sum(&a.union(&b)) + sum(&a.intersection(&b)));
+            let per_range = a.iter().map(|(min, max)| rule.sum_invalid(min, max, 10).unwrap()).sum::<u128>();
+            prop_assert_eq!(per_range, sum(&a));
+        }
+    }
+}
@@ -0,0 +1,43 @@
+use std::hint::black_box;
+
+use criterion::{criterion_group, criterion_main, Criterion, Throughput};
+use day_03::parse::parse_banks;
+
+// the original parser, a line at a time with no check that the chars are digits
+fn process_line(line: &str) -> Vec<usize> {
+    line.chars().map(|ch| ch as usize - 0x30).collect::<Vec<usize>>()
+}
+
+// a made up input far bigger than the puzzle's, 2000 banks of 5000 batteries
+fn large_input() -> String {
+    let mut seed = 42u64;
+    let mut text = String::new();
+
+    for _ in 0..2000 {
+        for _ in 0..5000 {
+            seed = seed.wrapping_mul(6364136223846793005).wrapping_add(1442695040888963407);
+            text.push((b'1' + ((seed >> 33) % 9) as u8) as char);
+        }
+        text.push('\n');
+    }
+
+    text
+}
+
+fn bench_parse(c: &mut Criterion) {
+    let text = large_input();
+
+    let mut group = c.benchmark_group("day-03 parse");
+    group.sample_size(10);
+    group.throughput(Throughput::Bytes(text.len() as u64));
+
+    group.bench_function("process_line per line", |b| {
+        b.iter(|| black_box(&text).lines().map(process_line).collect::<Vec<Vec<usize>>>())
+    });
+    group.bench_function("parse_banks", |b| b.iter(|| parse_banks(black_box(&text)).unwrap()));
+
+    group.finish();
+}
+
+criterion_group!(benches, bench_parse);
+criterion_main!(benches);
@@ -0,0 +1,262 @@
+use crate::select::{select_highest, Digit, Selection};
+
+/// Rules on which batteries in a bank can be turned on together
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Constraints {
+    /// Fewest positions from one chosen battery to the next, 1 allows neighbours and
+    /// 2 means no two chosen batteries are adjacent
+    pub min_gap: usize,
+    pub segments: Option<Segments>,
+}
+
+/// The bank cut into consecutive segments of `len` batteries (the last one may be shorter),
+/// each leaving at least `keep_off` of its batteries off
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Segments {
+    pub len: usize,
+    pub keep_off: usize,
+}
+
+impl Default for Constraints {
+    fn default() -> Self {
+        Constraints { min_gap: 1, segments: None }
+    }
+}
+
+impl Constraints {
+    pub fn no_adjacent() -> Self {
+        Constraints { min_gap: 2, ..Constraints::default() }
+    }
+
+    pub fn is_unconstrained(&self) -> bool {
+        self.min_gap <= 1 && self.segments.is_none_or(|s| s.keep_off == 0)
+    }
+}
+
+/// The largest number from `k` of the digits in `vals` that follows `constraints`, with the
+/// positions used. None if no k batteries can be turned on together.
+/// Without constraints this is the O(len) greedy `select_highest`, otherwise a DP over
+/// (position, digits left, digits taken in the current segment) that is O(len * k * segment len).
+pub fn select_constrained<D: Digit>(vals: &[D], k: usize, constraints: &Constraints) -> Option<Selection> {
+    if k > vals.len() {
+        return None
+    }
+    if constraints.is_unconstrained() {
+        return Some(select_highest(vals, k))
+    }
+
+    let table = Feasible::new(vals.len(), k, constraints);
+    if !table.get(0, k, 0) {
+        return None
+    }
+
+    let mut selection = Selection::default();
+    let (mut i, mut taken) = (0, 0);
+
+    for left in (1..=k).rev() {
+        // the biggest digit we can take next and still finish, the earliest one on a tie
+        // since it leaves at least as many choices for the rest
+        let mut best: Option<(usize, usize, usize)> = None;
+        let mut taken_at = taken;
+
+        for p in i..vals.len() {
+            if p > i && table.starts_segment(p) {
+                taken_at = 0;
+            }
+            if taken_at < table.cap(p) {
+                let (next, next_taken) = table.after_taking(p, taken_at);
+                if table.get(next, left - 1, next_taken) && best.is_none_or(|(b, _, _)| vals[p] > vals[b]) {
+                    best = Some((p, next, next_taken));
+                }
+            }
+        }
+
+        let (p, next, next_taken) = best.expect("the table said there's a way to finish");
+        selection.indices.push(p);
+        selection.digits.push(vals[p].into());
+        (i, taken) = (next, next_taken);
+    }
+
+    Some(selection)
+}
+
+// can[i][r][c]: r more batteries can be turned on from position i onwards, with c already
+// turned on in the segment holding i
+struct Feasible {
+    len: usize,
+    gap: usize,
+    segments: Option<Segments>,
+    depth: usize,
+    width: usize,
+    can: Vec<bool>,
+}
+
+impl Feasible {
+    fn new(len: usize, k: usize, constraints: &Constraints) -> Self {
+        let segments = constraints.segments.filter(|s| s.keep_off > 0).map(|s| Segments { len: s.len.max(1), ..s });
+        // without segments the count is never looked at, so it only needs the one value
+        let width = segments.map_or(1, |s| s.len.min(k) + 1);
+        let depth = k + 1;
+
+        let mut table = Feasible { len, gap: constraints.min_gap.max(1), segments, depth, width, can: vec![false; (len + 1) * depth * width] };
+
+        for c in 0..width {
+            table.set(len, 0, c);
+        }
+        for i in (0..len).rev() {
+            for r in 0..depth {
+                for c in 0..width {
+                    let skip = table.get(i + 1, r, if table.starts_segment(i + 1) { 0 } else { c });
+                    let take = r > 0 && c < table.cap(i) && {
+                        let (next, next_taken) = table.after_taking(i, c);
+                        table.get(next, r - 1, next_taken)
+                    };
+                    if skip || take {
+                        table.set(i, r, c);
+                    }
+                }
+            }
+        }
+
+        table
+    }
+
+    fn index(&self, i: usize, r: usize, c: usize) -> usize {
+        (i * self.depth + r) * self.width + c
+    }
+
+    fn get(&self, i: usize, r: usize, c: usize) -> bool {
+        // past the end of the bank only "nothing left to turn on" works
+        if i >= self.len { return r == 0 }
+        self.can[self.index(i, r, c.min(self.width - 1))]
+    }
+
+    fn set(&mut self, i: usize, r: usize, c: usize) {
+        let index = self.index(i, r, c);
+        self.can[index] = true;
+    }
+
+    fn starts_segment(&self, i: usize) -> bool {
+        self.segments.is_some_and(|s| i.is_multiple_of(s.len))
+    }
+
+    // most batteries that can be on in the segment holding i
+    fn cap(&self, i: usize) -> usize {
+        match self.segments {
+            Some(s) => {
+                let start = i / s.len * s.len;
+                let size = s.len.min(self.len - start);
+                size.saturating_sub(s.keep_off)
+            }
+            None => usize::MAX,
+        }
+    }
+
+    // where the next battery can be, and the segment count there, after turning on i
+    fn after_taking(&self, i: usize, taken: usize) -> (usize, usize) {
+        let next = i + self.gap;
+        match self.segments {
+            Some(s) if next / s.len == i / s.len => (next, taken + 1),
+            _ => (next, 0),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use proptest::prelude::*;
+
+    fn follows(indices: &[usize], len: usize, constraints: &Constraints) -> bool {
+        let gaps = indices.windows(2).all(|w| w[1] - w[0] >= constraints.min_gap);
+        let segments = constraints.segments.is_none_or(|s| {
+            (0..len).step_by(s.len).all(|start| {
+                let size = s.len.min(len - start);
+                let on = indices.iter().filter(|i| (start..start + size).contains(i)).count();
+                size - on >= s.keep_off.min(size)
+            })
+        });
+        gaps && segments
+    }
+
+    // tries every set of k positions
+    fn brute_force(vals: &[usize], k: usize, constraints: &Constraints) -> Option<Vec<usize>> {
+        (0u32..1 << vals.len())
+            .filter(|mask| mask.count_ones() as usize == k)
+            .map(|mask| (0..vals.len()).filter(|i| mask & (1 << i) != 0).collect::<Vec<usize>>())
+            .filter(|indices| follows(indices, vals.len(), constraints))
+            .map(|indices| indices.iter().map(|i| vals[*i]).collect::<Vec<usize>>())
+            .max()
+    }
+
+    #[test]
+    fn test_no_adjacent() -> anyhow::Result<()> {
+        let selection = select_constrained(&[9usize, 9, 1, 8, 7], 2, &Constraints::no_adjacent()).unwrap();
+        assert_eq!(vec![9, 8], selection.digits);
+        assert_eq!(vec![0, 3], selection.indices);
+
+        assert_eq!(None, select_constrained(&[1usize, 2, 3], 3, &Constraints::no_adjacent()));
+        Ok(())
+    }
+
+    #[test]
+    fn test_segments() -> anyhow::Result<()> {
+        // 2 segments of 3, each leaving one battery off
+        let constraints = Constraints { min_gap: 1, segments: Some(Segments { len: 3, keep_off: 1 }) };
+        let selection = select_constrained(&[9usize, 9, 9, 1, 1, 1], 4, &constraints).unwrap();
+        assert_eq!(vec![9, 9, 1, 1], selection.digits);
+
+        assert_eq!(None, select_constrained(&[9usize, 9, 9, 1, 1, 1], 5, &constraints));
+        Ok(())
+    }
+
+    #[test]
+    fn test_unconstrained_uses_greedy() -> anyhow::Result<()> {
+        let bank = [8u8, 1, 8, 1, 8, 1, 9, 1, 1, 1, 1, 2, 1, 1, 1];
+
+        assert_eq!(Some(select_highest(&bank, 12)), select_constrained(&bank, 12, &Constraints::default()));
+        assert_eq!(None, select_constrained(&bank, 16, &Constraints::default()));
+        Ok(())
+    }
+
+    #[test]
+    fn test_matches_brute_force() -> anyhow::Result<()> {
+        let mut seed = 99u64;
+        let mut next = || {
+            seed = seed.wrapping_mul(6364136223846793005).wrapping_add(1442695040888963407);
+            (seed >> 33) as usize
+        };
+
+        for _ in 0..200 {
+            let bank = (0..1 + next() % 11).map(|_| next() % 10).collect::<Vec<usize>>();
+            let k = next() % (bank.len() + 1);
+            let segments = (next() % 2 == 0).then(|| Segments { len: 1 + next() % 4, keep_off: next() % 3 });
+            let constraints = Constraints { min_gap: 1 + next() % 3, segments };
+
+            let selection = select_constrained(&bank, k, &constraints);
+            assert_eq!(brute_force(&bank, k, &constraints), selection.as_ref().map(|s| s.digits.clone()), "{bank:?} {k} {constraints:?}");
+            if let Some(selection) = selection {
+                assert!(follows(&selection.indices, bank.len(), &constraints), "{bank:?} {k} {constraints:?}");
+            }
+        }
+        Ok(())
+    }
+
+    fn constraints() -> impl Strategy<Value = Constraints> {
+        let segments = prop::option::of((1usize..5, 0usize..3).prop_map(|(len, keep_off)| Segments { len, keep_off }));
+        (1usize..4, segments).prop_map(|(min_gap, segments)| Constraints { min_gap, segments })
+    }
+
+    proptest! {
+        #[test]
+        fn prop_matches_brute_force(bank in prop::collection::vec(0usize..10, 1..12), k in 0usize..12, constraints in constraints()) {
+            let selection = select_constrained(&bank, k, &constraints);
+
+            prop_assert_eq!(brute_force(&bank, k, &constraints), selection.as_ref().map(|s| s.digits.clone()));
+            if let Some(selection) = selection {
+                prop_assert_eq!(k, selection.indices.len());
+                prop_assert!(follows(&selection.indices, bank.len(), &constraints));
+            }
+        }
+    }
+}
@@ -0,0 +1,51 @@
+use std::io::Write;
+
+use rand::{RngExt, SeedableRng};
+use rand_chacha::ChaCha8Rng;
+
+/// Writes `banks` random banks of `len` batteries, one per line with each battery's joltage
+/// from 1 to 9 like the puzzle input. The same seed always gives the same banks.
+pub fn generate_banks(out: &mut impl Write, banks: usize, len: usize, seed: u64) -> std::io::Result<()> {
+    let mut rng = ChaCha8Rng::seed_from_u64(seed);
+    let mut line = Vec::with_capacity(len + 1);
+
+    for _ in 0..banks {
+        line.clear();
+        line.extend((0..len).map(|_| rng.random_range(b'1'..=b'9')));
+        line.push(b'\n');
+        out.write_all(&line)?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse::parse_banks;
+    use crate::part2::sum_highest;
+
+    fn generate(banks: usize, len: usize, seed: u64) -> anyhow::Result<String> {
+        let mut out = vec![];
+        generate_banks(&mut out, banks, len, seed)?;
+        Ok(String::from_utf8(out)?)
+    }
+
+    #[test]
+    fn test_generate_banks() -> anyhow::Result<()> {
+        let banks = parse_banks(&generate(50, 100, 3)?)?;
+
+        assert_eq!(50, banks.len());
+        assert!(banks.iter().all(|bank| bank.len() == 100 && bank.iter().all(|d| (1..=9).contains(d))));
+        // every bank is long enough to turn on 12 batteries
+        assert!(sum_highest(banks.iter(), 12)? > 0u32.into());
+        Ok(())
+    }
+
+    #[test]
+    fn test_reproducible() -> anyhow::Result<()> {
+        assert_eq!(generate(20, 40, 42)?, generate(20, 40, 42)?);
+        assert_ne!(generate(20, 40, 42)?, generate(20, 40, 43)?);
+        assert!(generate(0, 10, 1)?.is_empty());
+        Ok(())
+    }
+}
@@ -1,2 +1,7 @@
+pub mod constrained;
+pub mod generate;
+pub mod parse;
 pub mod part1;
 pub mod part2;
+pub mod render;
+pub mod select;
@@ -1,25 +1,124 @@
+use day_03::constrained::{Constraints, Segments};
+use day_03::generate::generate_banks;
 use day_03::part1::process as process_part1;
-use day_03::part2::process as process_part2;
-use std::path::Path;
-use clap::Parser;
+use day_03::parse::read_banks;
+use day_03::part2::{find_highest_set_with, process as process_part2, sum_highest_with};
+use day_03::render::{render_bank, Highlight};
+use day_03::select::digits_to_string;
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::PathBuf;
+use anyhow::Context;
+use clap::{Parser, Subcommand};
 
-/// Simple program to greet a person
+/// Sums the highest joltage each bank of batteries can make
 #[derive(Parser, Debug)]
 #[command(version, about, long_about = None)]
 struct Args {
-    /// Name of the person to greet
+    /// Puzzle part, 1 turns on 2 batteries per bank and 2 turns on 12
+    #[arg(short, long, default_value_t = 1, value_parser = clap::value_parser!(u8).range(1..=2))]
+    part: u8,
+
+    /// Number of batteries to turn on in each bank, instead of the part's count
+    #[arg(short, long)]
+    digits: Option<usize>,
+
+    /// Don't turn on two neighbouring batteries, same as --min-gap 2
+    #[arg(long, conflicts_with = "min_gap")]
+    no_adjacent: bool,
+
+    /// Fewest positions between two batteries that are turned on
+    #[arg(long)]
+    min_gap: Option<usize>,
+
+    /// Cut each bank into segments of LEN batteries and leave KEEP_OFF of each one off
+    #[arg(long, value_name = "LEN:KEEP_OFF", value_parser = parse_segments)]
+    segments: Option<Segments>,
+
+    /// Print every bank with the batteries turned on highlighted
     #[arg(short, long)]
-    part: usize,
+    show: bool,
+
+    #[arg(short, long, default_value = "../inputs/day3.csv")]
+    input: PathBuf,
+
+    #[command(subcommand)]
+    command: Option<Command>,
 }
 
-fn main() {
+#[derive(Subcommand, Debug)]
+enum Command {
+    /// Write random banks of batteries, the same seed gives the same banks
+    Generate {
+        /// Number of banks
+        #[arg(short, long, default_value_t = 200)]
+        banks: usize,
+
+        /// Batteries in each bank
+        #[arg(short, long, default_value_t = 100)]
+        len: usize,
+
+        #[arg(short, long, default_value_t = 1)]
+        seed: u64,
+
+        /// File to write to instead of stdout
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+    },
+}
+
+fn parse_segments(text: &str) -> anyhow::Result<Segments> {
+    let (len, keep_off) = text.split_once(':').ok_or_else(|| anyhow::anyhow!("Expected LEN:KEEP_OFF, got {text:?}"))?;
+    let len = len.parse()?;
+    if len == 0 {
+        anyhow::bail!("Segments need at least one battery");
+    }
+    Ok(Segments { len, keep_off: keep_off.parse()? })
+}
+
+fn main() -> anyhow::Result<()> {
     let args = Args::parse();
 
-    if args.part == 1 {
-        let res = process_part1(&Path::new("../inputs/day3.csv"));
-        assert_eq!(res.unwrap(), 17031);
-    } else if args.part == 2 {
-        let res = process_part2(&Path::new("../inputs/day3.csv"));
-        assert_eq!(res.unwrap(), 168575096286051);
+    if let Some(Command::Generate { banks, len, seed, output }) = &args.command {
+        let mut out: Box<dyn Write> = match output {
+            Some(path) => Box::new(BufWriter::new(File::create(path).with_context(|| format!("Failed to create {}", path.display()))?)),
+            None => Box::new(BufWriter::new(std::io::stdout().lock())),
+        };
+        generate_banks(&mut out, *banks, *len, *seed)?;
+        out.flush()?;
+        return Ok(())
     }
+
+    let constraints = Constraints {
+        min_gap: args.min_gap.unwrap_or(if args.no_adjacent { 2 } else { 1 }),
+        segments: args.segments,
+    };
+
+    if args.show || args.digits.is_some() || !constraints.is_unconstrained() {
+        let digits = match (args.digits, args.part) {
+            (Some(digits), _) => digits,
+            (None, 1) => 2,
+            (None, _) => 12,
+        };
+        let banks = read_banks(&args.input)?;
+        let sum = sum_highest_with(banks.iter(), digits, &constraints)?;
+
+        if args.show {
+            let highlight = Highlight::for_stdout();
+            for bank in banks.iter() {
+                let selection = find_highest_set_with(bank, digits, &constraints).unwrap_or_default();
+                println!("{}  {}", render_bank(bank, &selection.indices, highlight), digits_to_string(&selection.digits));
+            }
+        }
+        println!("{sum}");
+        return Ok(())
+    }
+
+    match args.part {
+        1 => println!("{}", process_part1(&args.input)?),
+        2 => println!("{}", process_part2(&args.input)?),
+        part => anyhow::bail!("There's no part {part}, expected 1 or 2"),
+    }
+
+    Ok(())
 }
@@ -0,0 +1,217 @@
+use std::fmt::Display;
+use std::fs::read_to_string;
+use std::path::Path;
+
+use anyhow::Context;
+
+/// Every bank's digits back to back in one buffer, a byte per battery
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct Banks {
+    digits: Vec<u8>,
+    // where each bank starts in `digits`, plus the end of the last one
+    starts: Vec<usize>,
+}
+
+impl Banks {
+    pub fn len(&self) -> usize {
+        self.starts.len() - 1
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn get(&self, i: usize) -> Option<&[u8]> {
+        (i < self.len()).then(|| &self.digits[self.starts[i]..self.starts[i + 1]])
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &[u8]> {
+        self.starts.windows(2).map(|w| &self.digits[w[0]..w[1]])
+    }
+}
+
+/// A character in the input that isn't a digit, line and column count from 1
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InvalidDigit {
+    pub line: usize,
+    pub column: usize,
+    pub found: char,
+}
+
+impl Display for InvalidDigit {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Invalid digit {:?} at line {}, column {}", self.found, self.line, self.column)
+    }
+}
+
+impl std::error::Error for InvalidDigit {}
+
+/// Reads one bank per line, blank lines are skipped and \r\n line endings are fine
+pub fn parse_banks(text: &str) -> anyhow::Result<Banks> {
+    let mut banks = Banks { digits: Vec::with_capacity(text.len()), starts: vec![0] };
+
+    for (i, line) in text.lines().enumerate() {
+        if line.is_empty() { continue }
+
+        if let Err(at) = convert_digits(line.as_bytes(), &mut banks.digits) {
+            let column = line[..at].chars().count() + 1;
+            let found = line[at..].chars().next().expect("the bad byte is in the line");
+            return Err(InvalidDigit { line: i + 1, column, found }.into())
+        }
+        banks.starts.push(banks.digits.len());
+    }
+
+    Ok(banks)
+}
+
+pub fn read_banks(path: &Path) -> anyhow::Result<Banks> {
+    let text = read_to_string(path).with_context(|| format!("Failed to read {}", path.display()))?;
+
+    parse_banks(&text).with_context(|| format!("Failed to parse {}", path.display()))
+}
+
+/// Appends the value of each ASCII digit in `text` to `out`. On a byte that isn't a digit
+/// gives its index, `out` then holds the digits before it.
+pub fn convert_digits(text: &[u8], out: &mut Vec<u8>) -> Result<(), usize> {
+    out.reserve(text.len());
+
+    // SAFETY: every x86_64 cpu has sse2
+    #[cfg(target_arch = "x86_64")]
+    let done = unsafe { sse2::convert_blocks(text, out)? };
+    #[cfg(not(target_arch = "x86_64"))]
+    let done = 0;
+
+    convert_digits_scalar(&text[done..], out).map_err(|at| done + at)
+}
+
+/// The portable version of `convert_digits`, a byte at a time
+pub fn convert_digits_scalar(text: &[u8], out: &mut Vec<u8>) -> Result<(), usize> {
+    for (i, byte) in text.iter().enumerate() {
+        let digit = byte.wrapping_sub(b'0');
+        if digit > 9 {
+            return Err(i)
+        }
+        out.push(digit);
+    }
+    Ok(())
+}
+
+// SSE2 is part of every x86_64 cpu, so this needs no runtime detection
+#[cfg(target_arch = "x86_64")]
+mod sse2 {
+    use std::arch::x86_64::*;
+
+    const BLOCK: usize = 16;
+
+    /// Converts whole blocks of 16 bytes, gives how many bytes it got through. A block with
+    /// a bad byte converts the digits before it and reports where it is.
+    #[target_feature(enable = "sse2")]
+    pub fn convert_blocks(text: &[u8], out: &mut Vec<u8>) -> Result<usize, usize> {
+        let zero = _mm_set1_epi8(b'0' as i8);
+        let nine = _mm_set1_epi8(9);
+        let mut block = [0u8; BLOCK];
+
+        for (n, chunk) in text.chunks_exact(BLOCK).enumerate() {
+            // SAFETY: the chunk is exactly 16 bytes and loadu has no alignment needs
+            let bytes = unsafe { _mm_loadu_si128(chunk.as_ptr() as *const __m128i) };
+            let digits = _mm_sub_epi8(bytes, zero);
+            // a digit is at most 9 as an unsigned byte, anything else wraps above it
+            let ok = _mm_cmpeq_epi8(_mm_max_epu8(digits, nine), nine);
+            let mask = _mm_movemask_epi8(ok) as u32;
+
+            if mask != 0xffff {
+                let bad = (!mask).trailing_zeros() as usize;
+                out.extend(chunk[..bad].iter().map(|b| b - b'0'));
+                return Err(n * BLOCK + bad)
+            }
+            // SAFETY: `block` is 16 bytes and storeu has no alignment needs
+            unsafe { _mm_storeu_si128(block.as_mut_ptr() as *mut __m128i, digits) };
+            out.extend_from_slice(&block);
+        }
+
+        Ok(text.len() / BLOCK * BLOCK)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use proptest::prelude::*;
+
+    #[test]
+    fn test_parse_banks() -> anyhow::Result<()> {
+        let banks = parse_banks("987654321111111\r\n811111111111119\n\n234234234234278\n")?;
+
+        assert_eq!(3, banks.len());
+        assert_eq!(Some(&[9, 8, 7, 6, 5, 4, 3, 2, 1, 1, 1, 1, 1, 1, 1][..]), banks.get(0));
+        assert_eq!(Some(&[2, 3, 4, 2, 3, 4, 2, 3, 4, 2, 3, 4, 2, 7, 8][..]), banks.get(2));
+        assert_eq!(None, banks.get(3));
+        assert_eq!(vec![15, 15, 15], banks.iter().map(|b| b.len()).collect::<Vec<_>>());
+        assert!(parse_banks("")?.is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn test_invalid_digit() {
+        let err = parse_banks("12345\n12a45\n").unwrap_err();
+        assert_eq!(Some(&InvalidDigit { line: 2, column: 3, found: 'a' }), err.downcast_ref::<InvalidDigit>());
+        assert_eq!("Invalid digit 'a' at line 2, column 3", err.to_string());
+
+        // the column counts characters, not bytes
+        let err = parse_banks("1é2").unwrap_err();
+        assert_eq!("Invalid digit 'é' at line 1, column 2", err.to_string());
+
+        // past the first 16 byte block, and a lone \r in the middle of a line
+        let line = "1".repeat(40) + "\r" + "2";
+        let err = parse_banks(&line).unwrap_err();
+        assert_eq!("Invalid digit '\\r' at line 1, column 41", err.to_string());
+    }
+
+    #[test]
+    fn test_matches_scalar() {
+        let mut seed = 7u64;
+        let text = (0..1000).map(|_| {
+            seed = seed.wrapping_mul(6364136223846793005).wrapping_add(1442695040888963407);
+            // mostly digits with the odd byte from either side of them
+            match (seed >> 33) % 40 {
+                0 => b'/',
+                1 => b':',
+                n => b'0' + (n % 10) as u8,
+            }
+        }).collect::<Vec<u8>>();
+
+        for start in 0..40 {
+            for end in [start, start + 15, start + 16, start + 33, text.len()] {
+                let (mut fast, mut scalar) = (vec![], vec![]);
+                let slice = &text[start..end];
+                assert_eq!(convert_digits_scalar(slice, &mut scalar), convert_digits(slice, &mut fast), "{start}..{end}");
+                assert_eq!(scalar, fast, "{start}..{end}");
+            }
+        }
+
+        let digits = b"0123456789".repeat(10);
+        let mut out = vec![];
+        assert_eq!(Ok(()), convert_digits(&digits, &mut out));
+        assert_eq!((0..100).map(|i| i % 10).collect::<Vec<u8>>(), out);
+    }
+
+    proptest! {
+        #[test]
+        fn prop_parse_round_trip(banks in prop::collection::vec(prop::collection::vec(0u8..10, 1..120), 0..20), crlf in any::<bool>()) {
+            let text = banks.iter()
+                .map(|bank| bank.iter().map(|d| (b'0' + d) as char).collect::<String>())
+                .collect::<Vec<String>>()
+                .join(if crlf { "\r\n" } else { "\n" });
+
+            let parsed = parse_banks(&text).unwrap();
+            prop_assert_eq!(banks, parsed.iter().map(|bank| bank.to_vec()).collect::<Vec<_>>());
+        }
+
+        #[test]
+        fn prop_matches_scalar(text in prop::collection::vec(prop_oneof![8 => b'0'..=b'9', 1 => any::<u8>()], 0..100)) {
+            let (mut fast, mut scalar) = (vec![], vec![]);
+            prop_assert_eq!(convert_digits_scalar(&text, &mut scalar), convert_digits(&text, &mut fast));
+            prop_assert_eq!(scalar, fast);
+        }
+    }
+}
@@ -1,63 +1,60 @@
-use std::fs::read_to_string;
 use std::path::Path;
 
-pub fn read_txt(path: &Path) -> Vec<Vec<usize>> {
-    let file_text = read_to_string(path).expect("Failed to read file");
+use anyhow::{bail, Context};
+use num_bigint::BigUint;
+use num_traits::ToPrimitive;
 
-    file_text.lines().map(|line| process_line(line)).collect::<Vec<Vec<usize>>>()
-}
+use crate::constrained::{select_constrained, Constraints};
+use crate::parse::read_banks;
+use crate::select::{digits_to_number, highest_subsequence, select_highest, Digit, Selection};
 
-pub fn process_line(line: &str) -> Vec<usize> {
-    line.chars().map(|ch| ch as usize - 0x30).collect::<Vec<usize>>()
+pub fn find_highest_set<D: Digit>(vals: &[D], n: usize) -> Vec<usize> {
+    highest_subsequence(vals, n)
 }
 
-pub fn find_highest_set(vals: &Vec<usize>, n: usize) -> Vec<usize> {
-    /*
-    Two passes. first find the highest value, not including the last one
-    Then find the highest value following the current highest, including the last one
-     */
-
-    let mut values = vec![0; n];
+/// Same as `find_highest_set`, also giving the position of each chosen battery
+pub fn find_highest_positions<D: Digit>(vals: &[D], n: usize) -> Selection {
+    select_highest(vals, n)
+}
 
-    let mut index = 0;
-    for k in 0..n {
-        let mut highest = 0;
+/// The best `n` batteries that follow `constraints`, None if there's no way to turn on n
+pub fn find_highest_set_with<D: Digit>(vals: &[D], n: usize, constraints: &Constraints) -> Option<Selection> {
+    select_constrained(vals, n, constraints)
+}
 
-        let last_digit_to_search = vals.len() - (n - k) + 1;
-        for i in index..last_digit_to_search {
-            if vals[i] > highest {
-                highest = vals[i];
-                index = i+1;
-            }
+/// Sum over the banks of the largest number made from `n` of its batteries, exact for any n.
+/// An error names the first bank with fewer than `n` batteries.
+pub fn sum_highest<B: AsRef<[D]>, D: Digit>(data: impl IntoIterator<Item = B>, n: usize) -> anyhow::Result<BigUint> {
+    data.into_iter().enumerate().map(|(i, ls)| {
+        let ls = ls.as_ref();
+        if ls.len() < n {
+            bail!("Bank {} can't turn on {n} batteries, it only has {}", i + 1, ls.len())
         }
-        values[k] = highest;
-    }
-
-    return values
+        Ok(digits_to_number(&find_highest_set(ls, n)))
+    }).sum()
 }
 
-pub fn create_digit(values: Vec<usize>) -> i64 {
-    let n_digits = values.len() as u32;
-    let sum = values.iter().enumerate().map(|(i, val)| *val as i64 * (10 as i64).pow(n_digits - i as u32 - 1 as u32)).sum();
-
-    sum
+/// `sum_highest` with every bank following `constraints`, an error names the first bank
+/// that can't turn on `n` batteries
+pub fn sum_highest_with<B: AsRef<[D]>, D: Digit>(data: impl IntoIterator<Item = B>, n: usize, constraints: &Constraints) -> anyhow::Result<BigUint> {
+    data.into_iter().enumerate().map(|(i, ls)| {
+        let selection = find_highest_set_with(ls.as_ref(), n, constraints)
+            .with_context(|| format!("Bank {} can't turn on {n} batteries with {constraints:?}", i + 1))?;
+        Ok(digits_to_number(&selection.digits))
+    }).sum()
 }
 
 #[tracing::instrument]
 pub fn process(path: &Path) -> anyhow::Result<i64> {
-    let data = read_txt(path);
-
-    let sum = data.iter().map(|ls| {
-        let vals = find_highest_set(ls, 12);
-        create_digit(vals)
-    }).sum();
+    let banks = read_banks(path)?;
 
-    Ok(sum)
+    sum_highest(banks.iter(), 12)?.to_i64().context("The total joltage doesn't fit in an i64")
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::parse::parse_banks;
 
     #[test]
     fn test_process() -> anyhow::Result<()> {
@@ -75,62 +72,97 @@ mod tests {
         Ok(())
     }
 
-    #[test]
-    fn test_process_line() -> anyhow::Result<()> {
-        let res = process_line("0123456789");
-
-        assert_eq!(res, vec![0, 1, 2, 3, 4, 5, 6, 7, 8, 9]);
-        Ok(())
-    }
-
     #[test]
     fn test_find_highest_pair_last() -> anyhow::Result<()> {
-        let res = find_highest_set(&vec![0, 1, 2, 3], 2);
+        let res = find_highest_set(&[0usize, 1, 2, 3], 2);
         assert_eq!(res, vec![2, 3]);
         Ok(())
     }
 
     #[test]
     fn test_find_highest_pair_first() -> anyhow::Result<()> {
-        let res = find_highest_set(&vec![9, 8, 1, 2], 2);
+        let res = find_highest_set(&[9usize, 8, 1, 2], 2);
         assert_eq!(res, vec![9, 8]);
         Ok(())
     }
 
     #[test]
     fn test_find_highest_pair_same_digit() -> anyhow::Result<()> {
-        let res = find_highest_set(&vec![8, 9, 9, 1], 2);
+        let res = find_highest_set(&[8usize, 9, 9, 1], 2);
         assert_eq!(res, vec![9, 9]);
         Ok(())
     }
 
     #[test]
     fn test_find_highest_pair_second() -> anyhow::Result<()> {
-        let res = find_highest_set(&vec![8, 9, 4, 5, 8], 2);
+        let res = find_highest_set(&[8usize, 9, 4, 5, 8], 2);
         assert_eq!(res, vec![9, 8]);
         Ok(())
     }
 
     #[test]
     fn test_find_highest_trio() -> anyhow::Result<()> {
-        let res = find_highest_set(&vec![8, 9, 4, 5, 8], 3);
+        let res = find_highest_set(&[8usize, 9, 4, 5, 8], 3);
         assert_eq!(res, vec![9, 5, 8]);
         Ok(())
     }
 
     #[test]
     fn test_find_highest_quad() -> anyhow::Result<()> {
-        let res = find_highest_set(&vec![8, 9, 4, 1, 1, 5, 1, 1, 1, 8], 4);
+        let res = find_highest_set(&[8usize, 9, 4, 1, 1, 5, 1, 1, 1, 8], 4);
         assert_eq!(res, vec![9, 5, 1, 8]);
         Ok(())
     }
 
     #[test]
-    fn test_create_digit() -> anyhow::Result<()> {
-        let vals = vec![9, 5, 1, 8];
-        let num = create_digit(vals);
+    fn test_sum_highest() -> anyhow::Result<()> {
+        let data = parse_banks("987654321111111\n811111111111119\n234234234234278\n818181911112111")?;
+
+        assert_eq!(BigUint::from(98u32 + 89 + 78 + 92), sum_highest(data.iter(), 2)?);
+        assert_eq!(BigUint::from(3121910778619u64), sum_highest(data.iter(), 12)?);
+
+        let err = sum_highest(parse_banks("12345678901234\n9")?.iter(), 12).unwrap_err();
+        assert_eq!("Bank 2 can't turn on 12 batteries, it only has 1", err.to_string());
+        Ok(())
+    }
+
+    #[test]
+    fn test_sum_highest_wide() -> anyhow::Result<()> {
+        // choose 50 of 100 batteries, the answers have far more digits than an i64
+        let bank_1 = "1".repeat(50) + &"9".repeat(50);
+        let bank_2 = "9".repeat(49) + &"1".repeat(51);
+        let data = parse_banks(&format!("{bank_1}\n{bank_2}"))?;
+
+        let expected = "9".repeat(50).parse::<BigUint>()? + ("9".repeat(49) + "1").parse::<BigUint>()?;
+        assert_eq!(expected, sum_highest(data.iter(), 50)?);
+        assert_eq!(BigUint::from(2u32) * "9".repeat(19).parse::<BigUint>()?, sum_highest([vec![9usize; 30], vec![9; 19]], 19)?);
+        Ok(())
+    }
+
+    #[test]
+    fn test_find_highest_positions() -> anyhow::Result<()> {
+        let res = find_highest_positions(&[8usize, 9, 4, 1, 1, 5, 1, 1, 1, 8], 4);
+        assert_eq!(res.digits, vec![9, 5, 1, 8]);
+        assert_eq!(res.indices, vec![1, 5, 6, 9]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_find_highest_set_with() -> anyhow::Result<()> {
+        let res = find_highest_set_with(&[8u8, 9, 4, 5, 8], 3, &Constraints::no_adjacent()).unwrap();
+        assert_eq!(res.digits, vec![8, 4, 8]);
+        assert_eq!(find_highest_set_with(&[8u8, 9, 4, 5, 8], 3, &Constraints::default()).unwrap().digits, find_highest_set(&[8usize, 9, 4, 5, 8], 3));
+        Ok(())
+    }
+
+    #[test]
+    fn test_sum_highest_with() -> anyhow::Result<()> {
+        let data = parse_banks("987654321111111\n811111111111119")?;
 
-        assert_eq!(num, 9518);
+        assert_eq!(sum_highest(data.iter(), 2)?, sum_highest_with(data.iter(), 2, &Constraints::default())?);
+        assert_eq!(BigUint::from(97u32 + 89), sum_highest_with(data.iter(), 2, &Constraints::no_adjacent())?);
+        let err = sum_highest_with(data.iter(), 9, &Constraints::no_adjacent()).unwrap_err();
+        assert!(err.to_string().starts_with("Bank 1 can't turn on 9 batteries"));
         Ok(())
     }
 }
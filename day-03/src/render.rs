@@ -0,0 +1,77 @@
+use std::io::IsTerminal;
+
+use crate::select::{digits_to_string, Digit};
+
+const HIGHLIGHT: &str = "\x1b[1;32m";
+const RESET: &str = "\x1b[0m";
+
+/// How the turned on batteries stand out when a bank is printed
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Highlight {
+    Ansi,
+    Brackets,
+}
+
+impl Highlight {
+    /// Colour on a terminal, brackets when the output is piped or redirected
+    pub fn for_stdout() -> Highlight {
+        if std::io::stdout().is_terminal() { Highlight::Ansi } else { Highlight::Brackets }
+    }
+}
+
+/// The bank's digits with the ones at `indices` highlighted, runs of chosen digits are
+/// marked together, e.g. [98]7654321111[111]
+pub fn render_bank<D: Digit>(bank: &[D], indices: &[usize], highlight: Highlight) -> String {
+    let (open, close) = match highlight {
+        Highlight::Ansi => (HIGHLIGHT, RESET),
+        Highlight::Brackets => ("[", "]"),
+    };
+
+    let mut chosen = vec![false; bank.len()];
+    for i in indices {
+        chosen[*i] = true;
+    }
+
+    let mut out = String::with_capacity(bank.len() * 2);
+    for (i, digit) in bank.iter().enumerate() {
+        let starts = chosen[i] && (i == 0 || !chosen[i - 1]);
+        let ends = chosen[i] && (i + 1 == bank.len() || !chosen[i + 1]);
+
+        if starts { out.push_str(open); }
+        out.push_str(&digits_to_string(&[(*digit).into()]));
+        if ends { out.push_str(close); }
+    }
+
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse::parse_banks;
+    use crate::select::select_highest;
+
+    #[test]
+    fn test_render_sample() -> anyhow::Result<()> {
+        let render = |line: &str, k: usize| {
+            let banks = parse_banks(line).unwrap();
+            let bank = banks.get(0).expect("one bank per line");
+            render_bank(bank, &select_highest(bank, k).indices, Highlight::Brackets)
+        };
+
+        assert_eq!("[98]7654321111111", render("987654321111111", 2));
+        assert_eq!("[8]1111111111111[9]", render("811111111111119", 2));
+        assert_eq!("[987654321111]111", render("987654321111111", 12));
+        assert_eq!("2342342342342[78]", render("234234234234278", 2));
+        assert_eq!("23[4]2[34234234278]", render("234234234234278", 12));
+        assert_eq!("[8]1[8]1[8]1[911112111]", render("818181911112111", 12));
+        Ok(())
+    }
+
+    #[test]
+    fn test_render_ansi() -> anyhow::Result<()> {
+        assert_eq!("1\x1b[1;32m9\x1b[0m2\x1b[1;32m3\x1b[0m", render_bank(&[1u8, 9, 2, 3], &[1, 3], Highlight::Ansi));
+        assert_eq!("123", render_bank(&[1u8, 2, 3], &[], Highlight::Ansi));
+        Ok(())
+    }
+}
@@ -0,0 +1,148 @@
+use num_bigint::BigUint;
+
+/// A battery's digit, stored as a usize per battery or packed into a u8
+pub trait Digit: Copy + Ord + Into<usize> {}
+
+impl<D: Copy + Ord + Into<usize>> Digit for D {}
+
+/// The batteries turned on in a bank, positions in increasing order with their digits
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct Selection {
+    pub indices: Vec<usize>,
+    pub digits: Vec<usize>,
+}
+
+/// The largest number that can be made from `k` of the digits in `vals`, keeping their
+/// order, i.e. the lexicographically largest subsequence of length k. Runs in O(len).
+/// A bank with k or fewer digits gives all of them.
+pub fn select_highest<D: Digit>(vals: &[D], k: usize) -> Selection {
+    if k >= vals.len() {
+        return Selection { indices: (0..vals.len()).collect(), digits: vals.iter().map(|d| (*d).into()).collect() }
+    }
+
+    // digits we can still drop without running out of digits for the rest of the number
+    let mut drops = vals.len() - k;
+    let mut stack: Vec<usize> = Vec::with_capacity(vals.len());
+
+    for (i, &val) in vals.iter().enumerate() {
+        // a bigger digit replaces the smaller ones before it while we can afford to drop them
+        while drops > 0 && stack.last().is_some_and(|top| vals[*top] < val) {
+            stack.pop();
+            drops -= 1;
+        }
+        stack.push(i);
+    }
+    stack.truncate(k);
+
+    Selection { digits: stack.iter().map(|i| vals[*i].into()).collect(), indices: stack }
+}
+
+pub fn highest_subsequence<D: Digit>(vals: &[D], k: usize) -> Vec<usize> {
+    select_highest(vals, k).digits
+}
+
+/// The digits as a decimal string, most significant first
+pub fn digits_to_string(digits: &[usize]) -> String {
+    digits.iter().map(|d| char::from_digit(*d as u32, 10).expect("a digit is below 10")).collect()
+}
+
+/// The digits as one exact number, however many of them there are
+pub fn digits_to_number(digits: &[usize]) -> BigUint {
+    let digits = digits.iter().map(|d| *d as u8).collect::<Vec<u8>>();
+    BigUint::from_radix_be(&digits, 10).expect("a digit is below 10")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use proptest::prelude::*;
+
+    // the old part2 approach, rescanning the remaining window for every digit
+    fn window_scan(vals: &[usize], n: usize) -> Vec<usize> {
+        let mut values = vec![];
+        let mut index = 0;
+
+        for k in 0..n {
+            let window = &vals[index..=vals.len() - (n - k)];
+            let highest = *window.iter().max().unwrap();
+            index += window.iter().position(|val| *val == highest).unwrap() + 1;
+            values.push(highest);
+        }
+
+        values
+    }
+
+    #[test]
+    fn test_highest_subsequence() -> anyhow::Result<()> {
+        assert_eq!(vec![9, 8], highest_subsequence(&[9usize, 8, 1, 2], 2));
+        assert_eq!(vec![9, 5, 8], highest_subsequence(&[8usize, 9, 4, 5, 8], 3));
+        assert_eq!(vec![9, 5, 1, 8], highest_subsequence(&[8usize, 9, 4, 1, 1, 5, 1, 1, 1, 8], 4));
+        assert_eq!(vec![4, 3, 4, 2, 3, 4, 2, 3, 4, 2, 7, 8], highest_subsequence(&[2usize, 3, 4, 2, 3, 4, 2, 3, 4, 2, 3, 4, 2, 7, 8], 12));
+        Ok(())
+    }
+
+    #[test]
+    fn test_select_highest() -> anyhow::Result<()> {
+        let selection = select_highest(&[8usize, 1, 8, 1, 8, 1, 9, 1, 1, 1, 1, 2, 1, 1, 1], 12);
+        assert_eq!(vec![8, 8, 8, 9, 1, 1, 1, 1, 2, 1, 1, 1], selection.digits);
+        assert_eq!(vec![0, 2, 4, 6, 7, 8, 9, 10, 11, 12, 13, 14], selection.indices);
+
+        // equal digits keep the earliest position
+        assert_eq!(vec![1, 2], select_highest(&[8usize, 9, 9, 1], 2).indices);
+        assert_eq!(vec![0, 1], select_highest(&[5usize, 6], 4).indices);
+        assert_eq!(selection, select_highest(&[8u8, 1, 8, 1, 8, 1, 9, 1, 1, 1, 1, 2, 1, 1, 1], 12));
+        Ok(())
+    }
+
+    #[test]
+    fn test_short_banks() -> anyhow::Result<()> {
+        assert_eq!(Vec::<usize>::new(), highest_subsequence(&[1usize, 2, 3], 0));
+        assert_eq!(vec![1, 2, 3], highest_subsequence(&[1usize, 2, 3], 3));
+        assert_eq!(vec![1, 2], highest_subsequence(&[1usize, 2], 5));
+        Ok(())
+    }
+
+    #[test]
+    fn test_digits_to_number() -> anyhow::Result<()> {
+        assert_eq!(BigUint::from(9518u32), digits_to_number(&[9, 5, 1, 8]));
+        assert_eq!(BigUint::from(0u32), digits_to_number(&[]));
+        assert_eq!("0095", digits_to_string(&[0, 0, 9, 5]));
+
+        let fifty_nines = "9".repeat(50);
+        assert_eq!(fifty_nines, digits_to_number(&[9; 50]).to_string());
+        assert_eq!(fifty_nines, digits_to_string(&[9; 50]));
+        Ok(())
+    }
+
+    #[test]
+    fn test_matches_window_scan() -> anyhow::Result<()> {
+        // a fixed pseudo random bank so the test doesn't need a rand dependency
+        let mut seed = 12345u64;
+        let bank = (0..200).map(|_| {
+            seed = seed.wrapping_mul(6364136223846793005).wrapping_add(1442695040888963407);
+            (seed >> 33) as usize % 10
+        }).collect::<Vec<usize>>();
+
+        for k in 1..=bank.len() {
+            assert_eq!(window_scan(&bank, k), highest_subsequence(&bank, k), "k = {k}");
+        }
+        Ok(())
+    }
+
+    proptest! {
+        #[test]
+        fn prop_selection_is_subsequence(bank in prop::collection::vec(0usize..10, 0..200), k in 0usize..30) {
+            let selection = select_highest(&bank, k);
+
+            prop_assert_eq!(k.min(bank.len()), selection.indices.len());
+            prop_assert!(selection.indices.windows(2).all(|w| w[0] < w[1]));
+            prop_assert_eq!(selection.indices.iter().map(|i| bank[*i]).collect::<Vec<usize>>(), selection.digits);
+        }
+
+        #[test]
+        fn prop_matches_window_scan(bank in prop::collection::vec(0usize..10, 1..100), k in 1usize..100) {
+            let k = k.min(bank.len());
+            prop_assert_eq!(window_scan(&bank, k), highest_subsequence(&bank, k));
+        }
+    }
+}
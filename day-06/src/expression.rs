@@ -0,0 +1,287 @@
+use std::fmt::Display;
+use std::iter::Peekable;
+use std::str::FromStr;
+use std::vec::IntoIter;
+
+use anyhow::{bail, Context};
+
+use crate::grid::{Block, TextGrid};
+use crate::int::{checked_sum, Number};
+use crate::operators::OperatorRegistry;
+use crate::worksheet::ReadingMode;
+
+/// How tightly the operators of an expression bind
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Precedence {
+    /// Every operator the same, worked out left to right, 2 + 3 * 4 is 20
+    #[default]
+    LeftToRight,
+    /// `^` (right to left) over `*`, `/` and the word operators over `+` and `-`, 2 + 3 * 4 is 14
+    Standard,
+}
+
+impl Precedence {
+    pub const ALL: [Precedence; 2] = [Precedence::LeftToRight, Precedence::Standard];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Precedence::LeftToRight => "left-to-right",
+            Precedence::Standard => "standard",
+        }
+    }
+
+    // (left, right) binding power, the bigger side pulls the operand next to it
+    fn binding_power(&self, symbol: &str) -> (u8, u8) {
+        match (self, symbol) {
+            (Precedence::LeftToRight, _) => (1, 2),
+            (Precedence::Standard, "+" | "-") => (1, 2),
+            (Precedence::Standard, "^") => (6, 5),
+            (Precedence::Standard, _) => (3, 4),
+        }
+    }
+}
+
+impl Display for Precedence {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+impl FromStr for Precedence {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match Precedence::ALL.into_iter().find(|p| p.name() == s) {
+            Some(precedence) => Ok(precedence),
+            None => bail!("Unknown precedence {s:?}, expected one of {}", Precedence::ALL.map(|p| p.name()).join(" ")),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Token {
+    Open,
+    Close,
+    Operator(String),
+}
+
+/// Splits an operator row into operators and parentheses. Operators are separated by spaces,
+/// parentheses don't need any, e.g. `(+) *`.
+pub fn tokenize(text: &str) -> Vec<Token> {
+    let mut tokens = vec![];
+    let mut symbol = String::new();
+    for c in text.chars() {
+        if c.is_whitespace() || c == '(' || c == ')' {
+            if !symbol.is_empty() {
+                tokens.push(Token::Operator(std::mem::take(&mut symbol)));
+            }
+            match c {
+                '(' => tokens.push(Token::Open),
+                ')' => tokens.push(Token::Close),
+                _ => {}
+            }
+        } else {
+            symbol.push(c);
+        }
+    }
+    if !symbol.is_empty() {
+        tokens.push(Token::Operator(symbol));
+    }
+    tokens
+}
+
+/// A problem's expression with its numbers filled in
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Expr<T: Number = usize> {
+    Number(T),
+    Apply(String, Box<Expr<T>>, Box<Expr<T>>),
+}
+
+impl<T: Number> Expr<T> {
+    pub fn evaluate(&self, registry: &OperatorRegistry<T>) -> anyhow::Result<T> {
+        match self {
+            Expr::Number(n) => Ok(n.clone()),
+            Expr::Apply(symbol, lhs, rhs) => registry.lookup(symbol)?.apply(&[lhs.evaluate(registry)?, rhs.evaluate(registry)?]),
+        }
+    }
+
+    fn fmt_operand(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Expr::Number(_) => write!(f, "{self}"),
+            Expr::Apply(..) => write!(f, "({self})"),
+        }
+    }
+}
+
+impl<T: Number> Display for Expr<T> {
+    /// Every operation but the outermost one in brackets, `2 + (3 * 4)`
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Expr::Number(n) => write!(f, "{n}"),
+            Expr::Apply(symbol, lhs, rhs) => {
+                lhs.fmt_operand(f)?;
+                write!(f, " {symbol} ")?;
+                rhs.fmt_operand(f)
+            }
+        }
+    }
+}
+
+// a Pratt parser over the operator row, taking the next number whenever it needs an operand
+struct Parser<T: Number> {
+    tokens: Peekable<IntoIter<Token>>,
+    numbers: IntoIter<T>,
+    precedence: Precedence,
+}
+
+impl<T: Number> Parser<T> {
+    fn expression(&mut self, min_power: u8) -> anyhow::Result<Expr<T>> {
+        let mut lhs = match self.tokens.peek() {
+            Some(Token::Open) => {
+                self.tokens.next();
+                let inner = self.expression(0)?;
+                if self.tokens.next() != Some(Token::Close) {
+                    bail!("Missing a ')'")
+                }
+                inner
+            }
+            _ => Expr::Number(self.numbers.next().context("Ran out of numbers")?),
+        };
+
+        loop {
+            let symbol = match self.tokens.peek() {
+                None | Some(Token::Close) => break,
+                Some(Token::Open) => bail!("Expected an operator before '('"),
+                Some(Token::Operator(symbol)) => symbol.clone(),
+            };
+            let (left, right) = self.precedence.binding_power(&symbol);
+            if left < min_power {
+                break
+            }
+            self.tokens.next();
+            let rhs = self.expression(right)?;
+            lhs = Expr::Apply(symbol, Box::new(lhs), Box::new(rhs));
+        }
+
+        Ok(lhs)
+    }
+}
+
+/// Fills the numbers, in order, into the operator row's expression. Every number has to be
+/// used, so n numbers need n - 1 operators.
+pub fn parse_expression<T: Number>(row: &str, numbers: Vec<T>, precedence: Precedence) -> anyhow::Result<Expr<T>> {
+    let count = numbers.len();
+    let mut parser = Parser { tokens: tokenize(row).into_iter().peekable(), numbers: numbers.into_iter(), precedence };
+
+    let expr = parser.expression(0).with_context(|| format!("Can't read {row:?} with {count} numbers"))?;
+    if parser.tokens.next().is_some() {
+        bail!("Unmatched ')' in {row:?}")
+    }
+    let left = parser.numbers.len();
+    if left > 0 {
+        bail!("{row:?} only uses {} of the {count} numbers", count - left)
+    }
+    Ok(expr)
+}
+
+/// A problem of the expression layout as it was read off the sheet, for `--explain`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WorkedExpression<T: Number = usize> {
+    pub block: Block,
+    pub expr: Expr<T>,
+    pub answer: T,
+}
+
+impl<T: Number> Display for WorkedExpression<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}: {} = {}", self.block, self.expr, self.answer)
+    }
+}
+
+/// Solves a sheet whose operator rows hold an operator between each pair of numbers
+pub fn work_expressions<T: Number>(grid: &TextGrid, mode: ReadingMode, precedence: Precedence, registry: &OperatorRegistry<T>) -> anyhow::Result<Vec<WorkedExpression<T>>> {
+    let ops = grid.height().checked_sub(1).context("The worksheet is empty")?;
+
+    mode.blocks(grid).into_iter().map(|block| {
+        let numbers = mode.read_numbers(grid, &block, ops)?;
+        let expr = parse_expression(&grid.cell(ops, &block), numbers, precedence).with_context(|| format!("Bad expression in {block}"))?;
+        let answer = expr.evaluate(registry)?;
+        Ok(WorkedExpression { block, expr, answer })
+    }).collect()
+}
+
+pub fn total_expressions<T: Number>(grid: &TextGrid, mode: ReadingMode, precedence: Precedence) -> anyhow::Result<T> {
+    let worked = work_expressions(grid, mode, precedence, &OperatorRegistry::default())?;
+    checked_sum(&worked.into_iter().map(|w| w.answer).collect::<Vec<T>>())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn evaluate(row: &str, numbers: &[usize], precedence: Precedence) -> anyhow::Result<usize> {
+        parse_expression(row, numbers.to_vec(), precedence)?.evaluate(&OperatorRegistry::default())
+    }
+
+    #[test]
+    fn test_tokenize() {
+        let op = |s: &str| Token::Operator(s.to_string());
+        assert_eq!(vec![Token::Open, op("+"), Token::Close, op("*")], tokenize("(+)*"));
+        assert_eq!(vec![op("min"), Token::Open, op("-"), Token::Close], tokenize(" min ( - ) "));
+        assert!(tokenize("   ").is_empty());
+    }
+
+    #[test]
+    fn test_precedence() -> anyhow::Result<()> {
+        assert_eq!(20, evaluate("+ *", &[2, 3, 4], Precedence::LeftToRight)?);
+        assert_eq!(14, evaluate("+ *", &[2, 3, 4], Precedence::Standard)?);
+        assert_eq!(10, evaluate("* +", &[2, 3, 4], Precedence::Standard)?);
+        assert_eq!(5, evaluate("- -", &[10, 3, 2], Precedence::Standard)?);
+        // ^ groups right to left, 2 ^ (3 ^ 2)
+        assert_eq!(512, evaluate("^ ^", &[2, 3, 2], Precedence::Standard)?);
+        assert_eq!(64, evaluate("^ ^", &[2, 3, 2], Precedence::LeftToRight)?);
+        assert_eq!(9, evaluate("+ max", &[1, 2, 8], Precedence::Standard)?);
+        assert_eq!(7, evaluate("", &[7], Precedence::Standard)?);
+        Ok(())
+    }
+
+    #[test]
+    fn test_parentheses() -> anyhow::Result<()> {
+        assert_eq!(20, evaluate("(+)*", &[2, 3, 4], Precedence::Standard)?);
+        assert_eq!(14, evaluate("+ (*)", &[2, 3, 4], Precedence::LeftToRight)?);
+        assert_eq!(8, evaluate("- (/)", &[10, 4, 2], Precedence::LeftToRight)?);
+
+        let expr = parse_expression("+ * (- ^)", vec![1usize, 2, 9, 3, 2], Precedence::Standard)?;
+        assert_eq!("1 + (2 * (9 - (3 ^ 2)))", expr.to_string());
+        Ok(())
+    }
+
+    #[test]
+    fn test_errors() {
+        let err = evaluate("+", &[1, 2, 3], Precedence::Standard).unwrap_err();
+        assert_eq!("\"+\" only uses 2 of the 3 numbers", err.to_string());
+        let err = evaluate("+ +", &[1, 2], Precedence::Standard).unwrap_err();
+        assert_eq!("Can't read \"+ +\" with 2 numbers", err.to_string());
+        assert_eq!("Ran out of numbers", err.root_cause().to_string());
+        assert!(evaluate("(+", &[1, 2], Precedence::Standard).is_err());
+        assert_eq!("Unmatched ')' in \"+)\"", evaluate("+)", &[1, 2], Precedence::Standard).unwrap_err().to_string());
+        assert!(evaluate("+ (", &[1, 2], Precedence::Standard).is_err());
+        assert_eq!("Division of 1 by zero", evaluate("/", &[1, 0], Precedence::Standard).unwrap_err().to_string());
+        assert!("bodmas".parse::<Precedence>().is_err());
+    }
+
+    #[test]
+    fn test_sheet() -> anyhow::Result<()> {
+        let grid = TextGrid::parse("200  10\n 30   4\n  4   2\n+ *  -(/)");
+
+        let worked = work_expressions::<usize>(&grid, ReadingMode::Rows, Precedence::Standard, &OperatorRegistry::default())?;
+        let lines = worked.iter().map(|w| w.to_string()).collect::<Vec<String>>();
+        assert_eq!(vec!["columns 1-3: 200 + (30 * 4) = 320", "columns 6-9: 10 - (4 / 2) = 8"], lines);
+
+        assert_eq!(928usize, total_expressions(&grid, ReadingMode::Rows, Precedence::LeftToRight)?);
+        // right to left the columns are 4, 3 and 2
+        let grid = TextGrid::parse("200\n 30\n  4\n+ *");
+        assert_eq!(10usize, total_expressions(&grid, ReadingMode::ColumnsRightToLeft, Precedence::Standard)?);
+        Ok(())
+    }
+}
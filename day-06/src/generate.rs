@@ -0,0 +1,178 @@
+use std::io::Write;
+
+use anyhow::bail;
+use rand::{RngExt, SeedableRng};
+use rand_chacha::ChaCha8Rng;
+
+use crate::operators::OperatorRegistry;
+
+/// Which side of its problem a number is lined up on
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Align {
+    Left,
+    Right,
+}
+
+/// A problem to write out, its numbers top to bottom
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SheetProblem {
+    pub numbers: Vec<(String, Align)>,
+    pub symbol: String,
+}
+
+/// Lays the problems out side by side like the puzzle input, each as wide as its widest
+/// number or operator and a blank column between them. A problem with fewer numbers than
+/// the others leaves blank cells under them.
+pub fn render_worksheet(problems: &[SheetProblem]) -> String {
+    let rows = problems.iter().map(|p| p.numbers.len()).max().unwrap_or(0);
+    let mut lines = vec![String::new(); rows + 1];
+
+    for (i, problem) in problems.iter().enumerate() {
+        let width = problem.numbers.iter().map(|(n, _)| n.len()).chain([problem.symbol.len()]).max().unwrap_or(1);
+
+        for (row, line) in lines.iter_mut().enumerate() {
+            if i > 0 {
+                line.push(' ');
+            }
+            let cell = match (row, problem.numbers.get(row)) {
+                (row, _) if row == rows => format!("{:<width$}", problem.symbol),
+                (_, Some((number, Align::Left))) => format!("{number:<width$}"),
+                (_, Some((number, Align::Right))) => format!("{number:>width$}"),
+                (_, None) => " ".repeat(width),
+            };
+            line.push_str(&cell);
+        }
+    }
+
+    lines.into_iter().map(|line| line + "\n").collect()
+}
+
+/// Writes a random worksheet of `problems` problems side by side, each with `rows` numbers of
+/// 1 to `max_digits` digits (1 to 9, no zeros) above one of the `operators`. Every number is
+/// lined up on the left or the right of its problem at random, so both parts read it
+/// differently. The same seed always gives the same sheet.
+///
+/// Operators other than `+` and `*` can make a sheet some reading modes can't solve, e.g. a
+/// `-` that goes below zero.
+pub fn generate_worksheet(out: &mut impl Write, problems: usize, rows: usize, max_digits: usize, operators: &[String], seed: u64) -> anyhow::Result<()> {
+    if operators.is_empty() {
+        bail!("A worksheet needs at least one operator");
+    }
+    let registry = OperatorRegistry::<usize>::default();
+    for symbol in operators {
+        registry.lookup(symbol)?;
+    }
+
+    let mut rng = ChaCha8Rng::seed_from_u64(seed);
+    let max_digits = max_digits.max(1);
+
+    let problems = (0..problems).map(|_| {
+        let numbers = (0..rows).map(|_| {
+            let digits = rng.random_range(1..=max_digits);
+            (0..digits).map(|_| char::from(rng.random_range(b'1'..=b'9'))).collect::<String>()
+        }).collect::<Vec<String>>();
+        let symbol = operators[rng.random_range(0..operators.len())].clone();
+        let numbers = numbers.into_iter()
+            .map(|number| (number, if rng.random_bool(0.5) { Align::Left } else { Align::Right }))
+            .collect();
+        SheetProblem { numbers, symbol }
+    }).collect::<Vec<SheetProblem>>();
+
+    write!(out, "{}", render_worksheet(&problems))?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::grid::TextGrid;
+    use crate::worksheet::{total, work_problems, ReadingMode};
+    use num_bigint::BigUint;
+    use proptest::prelude::*;
+
+    fn generate(problems: usize, rows: usize, max_digits: usize, operators: &[&str], seed: u64) -> anyhow::Result<String> {
+        let operators = operators.iter().map(|s| s.to_string()).collect::<Vec<String>>();
+        let mut out = vec![];
+        generate_worksheet(&mut out, problems, rows, max_digits, &operators, seed)?;
+        Ok(String::from_utf8(out)?)
+    }
+
+    #[test]
+    fn test_generate_worksheet() -> anyhow::Result<()> {
+        let grid = TextGrid::parse(&generate(100, 4, 4, &["+", "*"], 6)?);
+
+        assert_eq!(5, grid.height());
+        assert_eq!(100, grid.blocks().len());
+        let worked = work_problems::<usize>(&grid, ReadingMode::Rows, &OperatorRegistry::default())?;
+        assert!(worked.iter().all(|w| w.vals.len() == 4 && ["+", "*"].contains(&w.symbol.as_str())));
+        for mode in ReadingMode::ALL {
+            total::<u128>(&grid, mode)?;
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_long_operators() -> anyhow::Result<()> {
+        // single digits under a three letter operator leave columns with no digits in them
+        let grid = TextGrid::parse(&generate(20, 3, 1, &["max", "gcd"], 2)?);
+        assert_eq!(20, grid.blocks().len());
+        let worked = work_problems::<usize>(&grid, ReadingMode::Rows, &OperatorRegistry::default())?;
+        assert!(worked.iter().all(|w| w.vals.len() == 3 && w.answer <= 9));
+        for mode in ReadingMode::ALL {
+            total::<usize>(&grid, mode)?;
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_reproducible() -> anyhow::Result<()> {
+        assert_eq!(generate(50, 4, 3, &["+", "*"], 42)?, generate(50, 4, 3, &["+", "*"], 42)?);
+        assert_ne!(generate(50, 4, 3, &["+", "*"], 42)?, generate(50, 4, 3, &["+", "*"], 43)?);
+        Ok(())
+    }
+
+    #[test]
+    fn test_bad_operators() {
+        assert!(generate(5, 3, 3, &[], 1).is_err());
+        assert!(generate(5, 3, 3, &["+", "%"], 1).unwrap_err().to_string().contains("%"));
+    }
+
+    fn sheet() -> impl Strategy<Value = Vec<SheetProblem>> {
+        let align = prop_oneof![Just(Align::Left), Just(Align::Right)];
+        let number = (0u64..1_000_000_000_000, align).prop_map(|(n, align)| (n.to_string(), align));
+        // operators that can't fail whatever the numbers are
+        let symbol = prop::sample::select(vec!["+", "*", "max", "min", "gcd", "lcm", "avg"]).prop_map(String::from);
+        let problem = (prop::collection::vec(number, 1..5), symbol).prop_map(|(numbers, symbol)| SheetProblem { numbers, symbol });
+        prop::collection::vec(problem, 1..12)
+    }
+
+    proptest! {
+        #[test]
+        fn prop_render_round_trip(problems in sheet()) {
+            let grid = TextGrid::parse(&render_worksheet(&problems));
+            let worked = work_problems::<BigUint>(&grid, ReadingMode::Rows, &OperatorRegistry::default()).unwrap();
+
+            prop_assert_eq!(problems.len(), worked.len());
+            for (problem, worked) in problems.iter().zip(&worked) {
+                prop_assert_eq!(&problem.symbol, &worked.symbol);
+                let numbers = problem.numbers.iter().map(|(n, _)| n.clone()).collect::<Vec<String>>();
+                prop_assert_eq!(numbers, worked.vals.iter().map(|v| v.to_string()).collect::<Vec<String>>());
+            }
+        }
+
+        #[test]
+        fn prop_column_directions_agree(problems in sheet()) {
+            // left to right is right to left backwards, problems and numbers both
+            let grid = TextGrid::parse(&render_worksheet(&problems));
+            let registry = OperatorRegistry::<BigUint>::default();
+            let ltr = work_problems(&grid, ReadingMode::ColumnsLeftToRight, &registry).unwrap();
+            let mut rtl = work_problems(&grid, ReadingMode::ColumnsRightToLeft, &registry).unwrap();
+
+            rtl.reverse();
+            for (ltr, rtl) in ltr.iter().zip(&rtl) {
+                prop_assert_eq!(ltr.block, rtl.block);
+                prop_assert_eq!(ltr.vals.iter().rev().collect::<Vec<_>>(), rtl.vals.iter().collect::<Vec<_>>());
+            }
+        }
+    }
+}
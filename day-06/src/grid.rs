@@ -0,0 +1,199 @@
+use std::fmt::Display;
+use std::fs::read_to_string;
+use std::path::Path;
+
+use anyhow::Context;
+
+/// A worksheet as a rectangle of characters, rows that stop short (e.g. an editor
+/// trimmed their trailing spaces) are padded with spaces
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct TextGrid {
+    cells: Vec<char>,
+    width: usize,
+    height: usize,
+}
+
+/// The columns `start..end` of one problem, between columns that are blank all the way down
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Block {
+    pub start: usize,
+    pub end: usize,
+}
+
+impl Block {
+    pub fn columns(&self) -> std::ops::Range<usize> {
+        self.start..self.end
+    }
+}
+
+impl Display for Block {
+    /// The columns counting from 1, like an editor shows them
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.end - self.start {
+            1 => write!(f, "column {}", self.end),
+            _ => write!(f, "columns {}-{}", self.start + 1, self.end),
+        }
+    }
+}
+
+impl From<Vec<Vec<char>>> for TextGrid {
+    fn from(rows: Vec<Vec<char>>) -> Self {
+        let width = rows.iter().map(|row| row.len()).max().unwrap_or(0);
+        let mut cells = Vec::with_capacity(width * rows.len());
+        for row in &rows {
+            cells.extend_from_slice(row);
+            cells.resize(cells.len() + width - row.len(), ' ');
+        }
+        TextGrid { cells, width, height: rows.len() }
+    }
+}
+
+impl TextGrid {
+    /// One row per line, blank lines at the end are dropped
+    pub fn parse(text: &str) -> Self {
+        let mut rows = text.lines().map(|line| line.chars().collect::<Vec<char>>()).collect::<Vec<_>>();
+        while rows.last().is_some_and(|row| row.iter().all(|c| c.is_whitespace())) {
+            rows.pop();
+        }
+        TextGrid::from(rows)
+    }
+
+    pub fn read(path: &Path) -> anyhow::Result<Self> {
+        let text = read_to_string(path).with_context(|| format!("Failed to read {}", path.display()))?;
+        Ok(TextGrid::parse(&text))
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    pub fn get(&self, row: usize, column: usize) -> Option<char> {
+        (row < self.height && column < self.width).then(|| self.cells[row * self.width + column])
+    }
+
+    pub fn row(&self, i: usize) -> &[char] {
+        &self.cells[i * self.width..(i + 1) * self.width]
+    }
+
+    pub fn rows(&self) -> impl Iterator<Item = &[char]> {
+        (0..self.height).map(|i| self.row(i))
+    }
+
+    pub fn column(&self, j: usize) -> impl Iterator<Item = char> + '_ {
+        self.rows().map(move |row| row[j])
+    }
+
+    /// The columns as rows, top to bottom becoming left to right
+    pub fn transpose(&self) -> TextGrid {
+        let cells = (0..self.width).flat_map(|j| self.column(j)).collect();
+        TextGrid { cells, width: self.height, height: self.width }
+    }
+
+    pub fn is_blank_column(&self, j: usize) -> bool {
+        self.column(j).all(|c| c == ' ')
+    }
+
+    /// The problems of the sheet, left to right
+    pub fn blocks(&self) -> Vec<Block> {
+        let mut blocks = vec![];
+        let mut start = None;
+        for j in 0..=self.width {
+            match (start, j < self.width && !self.is_blank_column(j)) {
+                (None, true) => start = Some(j),
+                (Some(s), false) => {
+                    blocks.push(Block { start: s, end: j });
+                    start = None;
+                }
+                _ => {}
+            }
+        }
+        blocks
+    }
+
+    /// What row `i` holds inside the block, without the padding around it
+    pub fn cell(&self, i: usize, block: &Block) -> String {
+        self.row(i)[block.columns()].iter().collect::<String>().trim().to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use proptest::prelude::*;
+
+    fn sample() -> TextGrid {
+        // the operator row has had its trailing spaces trimmed
+        TextGrid::parse("123 328  51 64 \n 45 64  387 23 \n  6 98  215 314\n*   +   *   +\n\n")
+    }
+
+    #[test]
+    fn test_parse_pads_rows() {
+        let grid = sample();
+
+        assert_eq!((15, 4), (grid.width(), grid.height()));
+        assert_eq!("*   +   *   +  ", grid.row(3).iter().collect::<String>());
+        assert_eq!(Some(' '), grid.get(3, 14));
+        assert_eq!(None, grid.get(4, 0));
+        assert_eq!(vec!['4', '3', '1', ' '], grid.column(13).collect::<Vec<char>>());
+        assert_eq!(4, grid.rows().count());
+    }
+
+    #[test]
+    fn test_transpose() {
+        let grid = TextGrid::parse("12\n3");
+        let transposed = grid.transpose();
+
+        assert_eq!((2, 2), (transposed.width(), transposed.height()));
+        assert_eq!(&['1', '3'], transposed.row(0));
+        assert_eq!(&['2', ' '], transposed.row(1));
+        assert_eq!(grid, transposed.transpose());
+    }
+
+    #[test]
+    fn test_blocks() {
+        let grid = sample();
+
+        assert!(grid.is_blank_column(3));
+        assert!(!grid.is_blank_column(4));
+        let blocks = grid.blocks();
+        assert_eq!(vec![Block { start: 0, end: 3 }, Block { start: 4, end: 7 }, Block { start: 8, end: 11 }, Block { start: 12, end: 15 }], blocks);
+        assert_eq!("45", grid.cell(1, &blocks[0]));
+        assert_eq!("+", grid.cell(3, &blocks[3]));
+        assert_eq!("columns 13-15", blocks[3].to_string());
+        assert_eq!("column 2", Block { start: 1, end: 2 }.to_string());
+    }
+
+    #[test]
+    fn test_empty() {
+        let grid = TextGrid::parse("");
+        assert_eq!(0, grid.rows().count());
+        assert!(grid.blocks().is_empty());
+
+        let grid = TextGrid::from(vec![vec![], vec![]]);
+        assert_eq!(2, grid.rows().count());
+        assert!(grid.blocks().is_empty());
+    }
+
+    proptest! {
+        #[test]
+        fn prop_transpose_twice(lines in prop::collection::vec("[0-9 +*]{0,12}", 0..6)) {
+            let grid = TextGrid::parse(&lines.join("\n"));
+            prop_assert_eq!(&grid, &grid.transpose().transpose());
+        }
+
+        #[test]
+        fn prop_blocks_split_on_blank_columns(lines in prop::collection::vec("[0-9 ]{0,20}", 1..6)) {
+            let grid = TextGrid::parse(&lines.join("\n"));
+            let blocks = grid.blocks();
+
+            // the blocks cover every column that isn't blank, in order, with blanks between them
+            prop_assert!(blocks.windows(2).all(|w| w[0].end < w[1].start));
+            let covered = blocks.iter().flat_map(|b| b.columns()).collect::<Vec<usize>>();
+            prop_assert_eq!(covered, (0..grid.width()).filter(|j| !grid.is_blank_column(*j)).collect::<Vec<usize>>());
+        }
+    }
+}
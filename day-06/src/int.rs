@@ -0,0 +1,89 @@
+use std::fmt::{Debug, Display};
+
+use anyhow::bail;
+use num_bigint::BigUint;
+use num_integer::Integer;
+use num_traits::{CheckedAdd, CheckedMul, CheckedSub, FromPrimitive, ToPrimitive};
+
+/// Integer types a worksheet's numbers and answers can be worked out in
+pub trait Number:
+    Integer + Clone + Debug + Display + Send + Sync
+    + CheckedAdd + CheckedSub + CheckedMul + FromPrimitive + ToPrimitive + 'static
+{
+    /// Name used in error messages and for `--width`
+    const NAME: &'static str;
+}
+
+impl Number for usize {
+    const NAME: &'static str = "usize";
+}
+
+impl Number for u128 {
+    const NAME: &'static str = "u128";
+}
+
+impl Number for BigUint {
+    const NAME: &'static str = "big";
+}
+
+/// A number, or an answer, that is too big for the integer type it's being worked out in
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Overflow {
+    pub width: &'static str,
+    pub what: String,
+}
+
+impl Display for Overflow {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} doesn't fit in {}", self.what, self.width)
+    }
+}
+
+impl std::error::Error for Overflow {}
+
+pub fn overflow<T: Number>(what: &str) -> anyhow::Error {
+    Overflow { width: T::NAME, what: what.to_string() }.into()
+}
+
+pub fn from_usize<T: Number>(value: usize) -> T {
+    T::from_usize(value).expect("every number type holds a usize")
+}
+
+/// A number written in decimal, an `Overflow` if it has too many digits for `T`
+pub fn parse_number<T: Number>(text: &str) -> anyhow::Result<T> {
+    if text.is_empty() || !text.bytes().all(|b| b.is_ascii_digit()) {
+        bail!("Invalid number {text:?}")
+    }
+    T::from_str_radix(text, 10).map_err(|_| overflow::<T>(text))
+}
+
+/// The grand total of the answers
+pub fn checked_sum<T: Number>(vals: &[T]) -> anyhow::Result<T> {
+    vals.iter().try_fold(T::zero(), |acc, v| acc.checked_add(v).ok_or_else(|| overflow::<T>("the grand total")))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_number() -> anyhow::Result<()> {
+        assert_eq!(431usize, parse_number("431")?);
+        assert_eq!(u128::MAX, parse_number(&u128::MAX.to_string())?);
+        assert_eq!(BigUint::from(10u32).pow(40), parse_number(&format!("1{}", "0".repeat(40)))?);
+
+        let err = parse_number::<usize>("99999999999999999999").unwrap_err();
+        assert_eq!(Some(&Overflow { width: "usize", what: "99999999999999999999".to_string() }), err.downcast_ref::<Overflow>());
+        assert_eq!("Invalid number \"4a\"", parse_number::<usize>("4a").unwrap_err().to_string());
+        assert!(parse_number::<usize>("").is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_checked_sum() -> anyhow::Result<()> {
+        assert_eq!(4277556usize, checked_sum(&[33210, 490, 4243455, 401])?);
+        assert_eq!("the grand total doesn't fit in usize", checked_sum(&[usize::MAX, 1]).unwrap_err().to_string());
+        assert_eq!(usize::MAX as u128 + 1, checked_sum(&[usize::MAX as u128, 1])?);
+        Ok(())
+    }
+}
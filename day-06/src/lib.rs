@@ -1,2 +1,9 @@
+pub mod expression;
+pub mod generate;
+pub mod grid;
+pub mod int;
+pub mod operators;
 pub mod part1;
 pub mod part2;
+pub mod stream;
+pub mod worksheet;
@@ -1,25 +1,174 @@
-use day_06::part1::process as process_part1;
-use day_06::part2::process as process_part2;
-use std::path::Path;
-use clap::Parser;
+use day_06::expression::{work_expressions, Precedence};
+use day_06::generate::generate_worksheet;
+use day_06::grid::TextGrid;
+use day_06::int::{checked_sum, overflow, Number, Overflow};
+use day_06::operators::OperatorRegistry;
+use day_06::stream::{stream_problems, ColumnReader};
+use day_06::worksheet::{total, work_problems, ReadingMode};
+use num_bigint::BigUint;
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::PathBuf;
+use anyhow::Context;
+use clap::{Parser, Subcommand, ValueEnum};
 
-/// Simple program to greet a person
+/// Adds up the answers to every problem of a cephalopod maths worksheet
 #[derive(Parser, Debug)]
 #[command(version, about, long_about = None)]
 struct Args {
-    /// Name of the person to greet
-    #[arg(short, long)]
+    /// Puzzle part, 1 reads the numbers along the rows and 2 down the columns
+    #[arg(short, long, default_value_t = 1)]
     part: usize,
+
+    /// Read the sheet another way than the part does: rows, columns-rtl, columns-ltr or
+    /// columns-up (right to left with the digits bottom to top)
+    #[arg(short, long)]
+    read: Option<ReadingMode>,
+
+    #[arg(short, long, default_value = "../inputs/day6.txt")]
+    input: PathBuf,
+
+    /// Read each operator row as an expression with an operator between every two numbers,
+    /// worked out left-to-right or with standard precedence, brackets allowed
+    #[arg(long, value_name = "PRECEDENCE")]
+    expressions: Option<Precedence>,
+
+    /// Read the file a column at a time instead of all at once, for sheets too big for
+    /// memory. Problems come left to right whatever the reading mode.
+    #[arg(short, long)]
+    stream: bool,
+
+    /// Print every problem as it was read off the sheet, with its column span and answer
+    #[arg(short, long)]
+    explain: bool,
+
+    /// Integer type the answers are worked out in, auto moves up to a wider one on overflow
+    #[arg(short, long, value_enum, default_value_t = Width::Auto)]
+    width: Width,
+
+    #[command(subcommand)]
+    command: Option<Command>,
+}
+
+#[derive(Subcommand, Debug)]
+enum Command {
+    /// Write a random worksheet, the same seed gives the same sheet
+    Generate {
+        /// Number of problems
+        #[arg(short, long, default_value_t = 1000)]
+        problems: usize,
+
+        /// Numbers in each problem
+        #[arg(short, long, default_value_t = 4)]
+        rows: usize,
+
+        /// Most digits a number has
+        #[arg(short, long, default_value_t = 4)]
+        max_digits: usize,
+
+        /// Operators to pick from, separated by spaces
+        #[arg(long, value_delimiter = ' ', default_value = "+ *")]
+        operators: Vec<String>,
+
+        #[arg(short, long, default_value_t = 1)]
+        seed: u64,
+
+        /// File to write to instead of stdout
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+    },
 }
 
-fn main() {
+#[derive(ValueEnum, Debug, Clone, Copy, PartialEq, Eq)]
+enum Width {
+    Auto,
+    Usize,
+    U128,
+    Big,
+}
+
+fn main() -> anyhow::Result<()> {
     let args = Args::parse();
 
-    if args.part == 1 {
-        let res = process_part1(&Path::new("../inputs/day6.txt"));
-        assert_eq!(res.unwrap(), 6605396225322);
-    } else if args.part == 2 {
-        let res = process_part2(&Path::new("../inputs/day6.txt"));
-        assert_eq!(res.unwrap(), 11052310600986);
+    if let Some(Command::Generate { problems, rows, max_digits, operators, seed, output }) = &args.command {
+        let mut out: Box<dyn Write> = match output {
+            Some(path) => Box::new(BufWriter::new(File::create(path).with_context(|| format!("Failed to create {}", path.display()))?)),
+            None => Box::new(BufWriter::new(std::io::stdout().lock())),
+        };
+        generate_worksheet(&mut out, *problems, *rows, *max_digits, operators, *seed)?;
+        out.flush()?;
+        return Ok(())
     }
+
+    // a streamed --explain prints as it goes, so it can't start over in a wider type
+    let width = match args.width {
+        Width::Auto if args.stream && args.explain => Width::Big,
+        width => width,
+    };
+
+    let output = match width {
+        Width::Usize => run::<usize>(&args)?,
+        Width::U128 => run::<u128>(&args)?,
+        Width::Big => run::<BigUint>(&args)?,
+        Width::Auto => match run::<usize>(&args) {
+            Err(err) if is_overflow(&err) => match run::<u128>(&args) {
+                Err(err) if is_overflow(&err) => run::<BigUint>(&args)?,
+                output => output?,
+            },
+            output => output?,
+        },
+    };
+
+    print!("{output}");
+    Ok(())
+}
+
+// a number that doesn't fit comes back wrapped in the context of where it was
+fn is_overflow(err: &anyhow::Error) -> bool {
+    err.chain().any(|cause| cause.is::<Overflow>())
+}
+
+// works out everything before printing so a run that overflows leaves no partial output
+fn run<T: Number>(args: &Args) -> anyhow::Result<String> {
+    let mode = match args.read {
+        Some(mode) => mode,
+        None => ReadingMode::for_part(args.part)?,
+    };
+
+    if args.stream {
+        if args.expressions.is_some() {
+            anyhow::bail!("--stream doesn't read the expression layout");
+        }
+        let mut total = T::zero();
+        let mut stdout = std::io::stdout().lock();
+        stream_problems(&mut ColumnReader::open(&args.input)?, mode, &OperatorRegistry::default(), |worked| {
+            if args.explain {
+                writeln!(stdout, "{worked}")?;
+            }
+            total = total.checked_add(&worked.answer).ok_or_else(|| overflow::<T>("the grand total"))?;
+            Ok(())
+        })?;
+        return Ok(format!("{total}\n"))
+    }
+
+    let grid = TextGrid::read(&args.input)?;
+
+    if let Some(precedence) = args.expressions {
+        let worked = work_expressions::<T>(&grid, mode, precedence, &OperatorRegistry::default())?;
+        let answers = worked.iter().map(|w| w.answer.clone()).collect::<Vec<T>>();
+        let lines = match args.explain {
+            true => worked.iter().map(|w| format!("{w}\n")).collect::<String>(),
+            false => String::new(),
+        };
+        return Ok(format!("{lines}{}\n", checked_sum(&answers)?))
+    }
+
+    if args.explain {
+        let worked = work_problems::<T>(&grid, mode, &OperatorRegistry::default())?;
+        let answers = worked.iter().map(|w| w.answer.clone()).collect::<Vec<T>>();
+        let lines = worked.iter().map(|w| format!("{w}\n")).collect::<String>();
+        return Ok(format!("{lines}{}\n", checked_sum(&answers)?))
+    }
+
+    Ok(format!("{}\n", total::<T>(&grid, mode)?))
 }
@@ -0,0 +1,371 @@
+use std::collections::BTreeMap;
+use std::fmt::Debug;
+
+use anyhow::{bail, Context};
+
+use crate::int::{from_usize, overflow, Number};
+
+/// Combines the numbers of one worksheet problem into its answer
+pub trait Operator<T: Number = usize>: Debug + Send + Sync {
+    /// The token for the operator on the worksheet's last row
+    fn symbol(&self) -> &str;
+
+    fn apply(&self, vals: &[T]) -> anyhow::Result<T>;
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Add;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Multiply;
+
+impl<T: Number> Operator<T> for Add {
+    fn symbol(&self) -> &str {
+        "+"
+    }
+
+    fn apply(&self, vals: &[T]) -> anyhow::Result<T> {
+        vals.iter().try_fold(T::zero(), |acc, v| acc.checked_add(v).ok_or_else(|| overflow::<T>(&format!("{acc} + {v}"))))
+    }
+}
+
+impl<T: Number> Operator<T> for Multiply {
+    fn symbol(&self) -> &str {
+        "*"
+    }
+
+    fn apply(&self, vals: &[T]) -> anyhow::Result<T> {
+        vals.iter().try_fold(T::one(), |acc, v| acc.checked_mul(v).ok_or_else(|| overflow::<T>(&format!("{acc} * {v}"))))
+    }
+}
+
+/// `-`, the first number minus all the others, an error if that goes below zero
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Subtract;
+
+/// How `/` and `avg` round a result that isn't a whole number
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Rounding {
+    /// Towards zero, like integer division
+    #[default]
+    Down,
+    Up,
+    /// To the closest whole number, halves round up
+    Nearest,
+}
+
+impl Rounding {
+    pub fn divide<T: Number>(&self, numerator: &T, denominator: &T) -> anyhow::Result<T> {
+        if denominator.is_zero() {
+            bail!("Division of {numerator} by zero")
+        }
+        let (quotient, remainder) = numerator.div_rem(denominator);
+        let round_up = match self {
+            Rounding::Down => false,
+            Rounding::Up => !remainder.is_zero(),
+            Rounding::Nearest => remainder >= denominator.clone() - remainder.clone(),
+        };
+        Ok(if round_up { quotient + T::one() } else { quotient })
+    }
+}
+
+/// `/`, the first number divided by each of the others in turn
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Divide(pub Rounding);
+
+/// `^`, a tower of powers read right to left like in maths, 2 3 2 is 2^(3^2)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Power;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Min;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Max;
+
+/// `gcd`, the greatest common divisor, 0 for no numbers
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Gcd;
+
+/// `lcm`, the least common multiple, 1 for no numbers and 0 if any number is 0
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Lcm;
+
+/// `avg`, the mean of the numbers rounded the given way
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Average(pub Rounding);
+
+impl<T: Number> Operator<T> for Subtract {
+    fn symbol(&self) -> &str {
+        "-"
+    }
+
+    fn apply(&self, vals: &[T]) -> anyhow::Result<T> {
+        let (first, rest) = vals.split_first().context("Nothing to subtract from")?;
+        rest.iter().try_fold(first.clone(), |acc, v| {
+            acc.checked_sub(v).with_context(|| format!("{acc} - {v} is below zero"))
+        })
+    }
+}
+
+impl<T: Number> Operator<T> for Divide {
+    fn symbol(&self) -> &str {
+        "/"
+    }
+
+    fn apply(&self, vals: &[T]) -> anyhow::Result<T> {
+        let (first, rest) = vals.split_first().context("Nothing to divide")?;
+        rest.iter().try_fold(first.clone(), |acc, v| self.0.divide(&acc, v))
+    }
+}
+
+impl<T: Number> Operator<T> for Power {
+    fn symbol(&self) -> &str {
+        "^"
+    }
+
+    fn apply(&self, vals: &[T]) -> anyhow::Result<T> {
+        let (last, rest) = vals.split_last().context("No numbers to raise")?;
+        rest.iter().rev().try_fold(last.clone(), |exp, base| {
+            // 0 and 1 stay put and anything to the 0 is 1, however big the power is
+            if exp.is_zero() || base.is_one() {
+                return Ok(T::one())
+            }
+            if base.is_zero() {
+                return Ok(T::zero())
+            }
+            // anything else to a power past u32::MAX doesn't fit in memory either
+            exp.to_u32()
+                .and_then(|e| num_traits::checked_pow(base.clone(), e as usize))
+                .ok_or_else(|| overflow::<T>(&format!("{base} ^ {exp}")))
+        })
+    }
+}
+
+impl<T: Number> Operator<T> for Min {
+    fn symbol(&self) -> &str {
+        "min"
+    }
+
+    fn apply(&self, vals: &[T]) -> anyhow::Result<T> {
+        vals.iter().min().cloned().context("No numbers to take the min of")
+    }
+}
+
+impl<T: Number> Operator<T> for Max {
+    fn symbol(&self) -> &str {
+        "max"
+    }
+
+    fn apply(&self, vals: &[T]) -> anyhow::Result<T> {
+        vals.iter().max().cloned().context("No numbers to take the max of")
+    }
+}
+
+impl<T: Number> Operator<T> for Gcd {
+    fn symbol(&self) -> &str {
+        "gcd"
+    }
+
+    fn apply(&self, vals: &[T]) -> anyhow::Result<T> {
+        Ok(vals.iter().fold(T::zero(), |acc, v| acc.gcd(v)))
+    }
+}
+
+impl<T: Number> Operator<T> for Lcm {
+    fn symbol(&self) -> &str {
+        "lcm"
+    }
+
+    fn apply(&self, vals: &[T]) -> anyhow::Result<T> {
+        vals.iter().try_fold(T::one(), |acc, v| {
+            if acc.is_zero() || v.is_zero() {
+                return Ok(T::zero())
+            }
+            (acc.clone() / acc.gcd(v)).checked_mul(v).ok_or_else(|| overflow::<T>(&format!("lcm of {acc} and {v}")))
+        })
+    }
+}
+
+impl<T: Number> Operator<T> for Average {
+    fn symbol(&self) -> &str {
+        "avg"
+    }
+
+    fn apply(&self, vals: &[T]) -> anyhow::Result<T> {
+        if vals.is_empty() {
+            bail!("No numbers to average")
+        }
+        let sum = Add.apply(vals).map_err(|_| overflow::<T>("the sum to average"))?;
+        self.0.divide(&sum, &from_usize(vals.len()))
+    }
+}
+
+/// The operators a worksheet can use, looked up by their symbol
+#[derive(Debug)]
+pub struct OperatorRegistry<T: Number = usize> {
+    operators: BTreeMap<String, Box<dyn Operator<T>>>,
+}
+
+impl<T: Number> Default for OperatorRegistry<T> {
+    /// The puzzle's `+` and `*`, and `-`, `/`, `^`, `min`, `max`, `gcd`, `lcm` and `avg`
+    /// with division and averages rounding down
+    fn default() -> Self {
+        let mut registry = OperatorRegistry::empty();
+        registry.register(Box::new(Add));
+        registry.register(Box::new(Multiply));
+        registry.register(Box::new(Subtract));
+        registry.register(Box::new(Divide(Rounding::Down)));
+        registry.register(Box::new(Power));
+        registry.register(Box::new(Min));
+        registry.register(Box::new(Max));
+        registry.register(Box::new(Gcd));
+        registry.register(Box::new(Lcm));
+        registry.register(Box::new(Average(Rounding::Down)));
+        registry
+    }
+}
+
+impl<T: Number> OperatorRegistry<T> {
+    pub fn empty() -> Self {
+        OperatorRegistry { operators: BTreeMap::new() }
+    }
+
+    /// Adds an operator, replacing any already registered under the same symbol
+    pub fn register(&mut self, operator: Box<dyn Operator<T>>) {
+        self.operators.insert(operator.symbol().to_string(), operator);
+    }
+
+    pub fn get(&self, symbol: &str) -> Option<&dyn Operator<T>> {
+        self.operators.get(symbol).map(|op| op.as_ref())
+    }
+
+    /// Like `get`, with an error listing the known symbols when there's no such operator
+    pub fn lookup(&self, symbol: &str) -> anyhow::Result<&dyn Operator<T>> {
+        match self.get(symbol) {
+            Some(operator) => Ok(operator),
+            None => bail!("Unknown operator {symbol:?}, expected one of {}", self.symbols().collect::<Vec<_>>().join(" ")),
+        }
+    }
+
+    pub fn symbols(&self) -> impl Iterator<Item = &str> {
+        self.operators.keys().map(|s| s.as_str())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::int::Overflow;
+    use num_bigint::BigUint;
+
+    #[derive(Debug)]
+    struct Count;
+
+    impl Operator for Count {
+        fn symbol(&self) -> &str {
+            "count"
+        }
+
+        fn apply(&self, vals: &[usize]) -> anyhow::Result<usize> {
+            Ok(vals.len())
+        }
+    }
+
+    fn apply(symbol: &str, vals: &[usize]) -> anyhow::Result<usize> {
+        OperatorRegistry::default().lookup(symbol)?.apply(vals)
+    }
+
+    #[test]
+    fn test_builtins() -> anyhow::Result<()> {
+        assert_eq!(33210, apply("*", &[123, 45, 6])?);
+        assert_eq!(490, apply("+", &[328, 64, 98])?);
+        assert_eq!(219, apply("-", &[328, 64, 45])?);
+        assert_eq!(5, apply("/", &[100, 4, 5])?);
+        assert_eq!(512, apply("^", &[2, 3, 2])?);
+        assert_eq!(23, apply("min", &[51, 64, 23])?);
+        assert_eq!(64, apply("max", &[51, 64, 23])?);
+        assert_eq!(6, apply("gcd", &[12, 18, 30])?);
+        assert_eq!(180, apply("lcm", &[12, 18, 30])?);
+        assert_eq!(46, apply("avg", &[51, 64, 23])?);
+        Ok(())
+    }
+
+    #[test]
+    fn test_errors() {
+        assert_eq!("64 - 65 is below zero", apply("-", &[100, 36, 65]).unwrap_err().to_string());
+        assert_eq!("Division of 25 by zero", apply("/", &[100, 4, 0]).unwrap_err().to_string());
+        assert_eq!("10 ^ 20 doesn't fit in usize", apply("^", &[10, 20]).unwrap_err().to_string());
+        assert_eq!("2 ^ 4294967296 doesn't fit in usize", apply("^", &[2, 1 << 32]).unwrap_err().to_string());
+        assert!(apply("lcm", &[usize::MAX, usize::MAX - 1]).is_err());
+        assert!(apply("avg", &[usize::MAX, 1]).is_err());
+        assert!(apply("min", &[]).is_err());
+        assert!(apply("avg", &[]).is_err());
+    }
+
+    #[test]
+    fn test_rounding() -> anyhow::Result<()> {
+        assert_eq!(3, Divide(Rounding::Down).apply(&[7usize, 2])?);
+        assert_eq!(4, Divide(Rounding::Up).apply(&[7usize, 2])?);
+        assert_eq!(4, Divide(Rounding::Nearest).apply(&[7usize, 2])?);
+        assert_eq!(2, Divide(Rounding::Nearest).apply(&[7usize, 3])?);
+        assert_eq!(3, Divide(Rounding::Up).apply(&[6usize, 2])?);
+
+        assert_eq!(2, Average(Rounding::Down).apply(&[1usize, 2, 4])?);
+        assert_eq!(3, Average(Rounding::Up).apply(&[1usize, 2, 4])?);
+        assert_eq!(2, Average(Rounding::Nearest).apply(&[1usize, 2, 4])?);
+        Ok(())
+    }
+
+    #[test]
+    fn test_edge_cases() -> anyhow::Result<()> {
+        assert_eq!(0, apply("lcm", &[4, 0, 6])?);
+        assert_eq!(1, apply("lcm", &[])?);
+        assert_eq!(0, apply("gcd", &[])?);
+        assert_eq!(7, apply("gcd", &[0, 7])?);
+        assert_eq!(1, apply("^", &[5, 0])?);
+        // powers too big for a u32 are fine when the base is 0 or 1
+        assert_eq!(1, apply("^", &[1, 5_000_000_000])?);
+        assert_eq!(0, apply("^", &[0, 5_000_000_000])?);
+        assert_eq!(1, apply("^", &[0, 0])?);
+        assert_eq!(1, apply("^", &[2, 0, 5_000_000_000])?);
+        assert_eq!(9, apply("-", &[9])?);
+        Ok(())
+    }
+
+    #[test]
+    fn test_overflow() -> anyhow::Result<()> {
+        // five 5 digit numbers, about 10^25
+        let column = [99999, 88888, 77777, 66666, 55555];
+        let err = apply("*", &column).unwrap_err();
+        assert_eq!(Some("usize"), err.downcast_ref::<Overflow>().map(|o| o.width));
+        assert!(apply("+", &[usize::MAX, 1]).unwrap_err().is::<Overflow>());
+
+        let product = 99999u128 * 88888 * 77777 * 66666 * 55555;
+        assert_eq!(product, Multiply.apply(&column.map(|v| v as u128))?);
+        assert_eq!(BigUint::from(product), Multiply.apply(&column.map(BigUint::from))?);
+
+        let big = Power.apply(&[BigUint::from(10u32), BigUint::from(40u32)])?;
+        assert_eq!(format!("1{}", "0".repeat(40)), big.to_string());
+        assert!(Power.apply(&[10u128, 40]).unwrap_err().is::<Overflow>());
+        Ok(())
+    }
+
+    #[test]
+    fn test_register() -> anyhow::Result<()> {
+        let mut registry = OperatorRegistry::default();
+        assert_eq!(
+            "Unknown operator \"count\", expected one of * + - / ^ avg gcd lcm max min",
+            registry.lookup("count").unwrap_err().to_string()
+        );
+
+        registry.register(Box::new(Count));
+        assert_eq!(3, registry.lookup("count")?.apply(&[51, 64, 23])?);
+
+        // a registered operator replaces the built in one with the same symbol
+        registry.register(Box::new(Divide(Rounding::Up)));
+        assert_eq!(4, registry.lookup("/")?.apply(&[7, 2])?);
+        assert!(OperatorRegistry::<usize>::empty().get("+").is_none());
+        Ok(())
+    }
+}
@@ -1,104 +1,119 @@
 use std::path::Path;
-use std::fs::read_to_string;
 
+use crate::grid::TextGrid;
+use crate::worksheet::{self, ReadingMode};
 
-trait Problem { 
-    fn solve(&self) -> usize;
-
-    fn create(vec: Vec<usize>) -> impl Problem;
-}
-pub struct MultiplicationProblem {
-    vals: Vec<usize>,
+#[tracing::instrument]
+pub fn process(input: &Path) -> anyhow::Result<usize> {
+    worksheet::total(&TextGrid::read(input)?, ReadingMode::Rows)
 }
 
-impl Problem for MultiplicationProblem {
-    fn solve(&self) -> usize {
-        let mut res = 1;
-        self.vals.iter().for_each(|v| res = res * v);
-        res
-    }
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::int::{Number, Overflow};
+    use crate::operators::OperatorRegistry;
+    use num_bigint::BigUint;
+
+    #[test]
+    fn test_process() -> anyhow::Result<()> {
+        let input = Path::new("../inputs/day6_sample.txt");
 
-    fn create(vec: Vec<usize>) -> impl Problem {
-        MultiplicationProblem { vals: vec }
+        assert_eq!(4277556, process(input)?);
+        Ok(())
     }
-}
 
-pub struct AdditionProblem {
-    vals: Vec<usize>,
-}
+    #[test]
+    fn test_process_full() -> anyhow::Result<()> {
+        let input = Path::new("../inputs/day6.txt");
 
-impl Problem for AdditionProblem {
-    fn solve(&self) -> usize {
-        self.vals.iter().sum()
+        assert_eq!(6605396225322, process(input)?);
+        Ok(())
     }
 
-    fn create(vec: Vec<usize>) -> impl Problem {
-        AdditionProblem { vals: vec }
+    fn grid(lines: &[&str]) -> TextGrid {
+        TextGrid::parse(&lines.join("\n"))
     }
-}
 
-pub enum Operator {
-    Multiply,
-    Add,
-}
-
-pub fn read_txt(path: &Path) -> Vec<Vec<String>> {
-    let file_text = read_to_string(path).expect("Failed to read file");
-
-    let mut problems = vec![];
+    fn sample() -> TextGrid {
+        grid(&["123 328  51 64 ", " 45 64  387 23 ", "  6 98  215 314", "*   +   *   +  "])
+    }
 
-    let mut lines = file_text.lines();
+    fn solve_problems(grid: &TextGrid) -> anyhow::Result<Vec<usize>> {
+        worksheet::solve_problems(grid, ReadingMode::Rows, &OperatorRegistry::default())
+    }
 
-    while let Some(line) = lines.next() {
-        problems.push(line.split_whitespace().map(|v| v.to_owned()).collect::<Vec<String>>());
+    fn total<T: Number>(grid: &TextGrid) -> anyhow::Result<T> {
+        worksheet::total(grid, ReadingMode::Rows)
     }
 
-    problems
-}
+    #[test]
+    fn test_trimmed_lines() -> anyhow::Result<()> {
+        // trailing spaces trimmed off the ends of the lines
+        let data = grid(&["123 328  51 64", " 45 64  387 23", "  6 98  215 314", "*   +   *   +"]);
 
-pub fn solve_problems(data: Vec<Vec<String>>) -> Vec<usize> {
-    let mut problems = vec![];
-    let n_vals = data.len() - 1;
-    for j in 0..data[0].len() {
-        let vals: Vec<usize> = (0..n_vals).map(|i| data[i][j].parse::<usize>().unwrap()).collect();
+        assert_eq!(vec![33210, 490, 4243455, 401], solve_problems(&data)?);
+        Ok(())
+    }
 
-        let value = match data[n_vals][j].as_str() {
-            "*" => MultiplicationProblem::create(vals).solve(),
-            "+" => AdditionProblem::create(vals).solve(),
-            _ => unreachable!()
-        };
+    #[test]
+    fn test_invalid_number() {
+        let data = grid(&["123 328  51 64", " 45 6x  387 23", "  6 98  215 314", "*   +   *   +"]);
 
-        problems.push(value)
+        let err = solve_problems(&data).unwrap_err();
+        assert_eq!("Invalid number in row 2", err.to_string());
     }
 
-    problems
-}
-
-#[tracing::instrument]
-pub fn process(input: &Path) -> anyhow::Result<usize> {
-    let data = read_txt(input);
-    let solutions = solve_problems(data);
+    #[test]
+    fn test_solve_problems() -> anyhow::Result<()> {
+        assert_eq!(vec![33210, 490, 4243455, 401], solve_problems(&sample())?);
+        Ok(())
+    }
 
-    Ok(solutions.iter().sum())
-}
+    #[test]
+    fn test_unknown_operator() {
+        let data = grid(&["123 328  51 64", " 45 64  387 23", "  6 98  215 314", "*   %   *   +"]);
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+        let err = solve_problems(&data).unwrap_err();
+        assert_eq!("Unknown operator \"%\", expected one of * + - / ^ avg gcd lcm max min", err.to_string());
+    }
 
     #[test]
-    fn test_process() -> anyhow::Result<()> {
-        let input = Path::new("../inputs/day6_sample.txt");
+    fn test_operators() -> anyhow::Result<()> {
+        let data = grid(&[
+            "100  96 2  12  12  12  12  12",
+            " 30   4 3  18  18  18  18  19",
+            "  5   5 2  30  30  30  30  30",
+            "-   /   ^ min max gcd lcm avg",
+        ]);
+
+        assert_eq!(vec![65, 4, 512, 12, 30, 6, 180, 20], solve_problems(&data)?);
+        Ok(())
+    }
 
-        assert_eq!(4277556, process(&input)?);
+    #[test]
+    fn test_total_overflow() -> anyhow::Result<()> {
+        let data = grid(&["99999 1", "88888 2", "77777 3", "66666 4", "55555 5", "*     +"]);
+        let product = 99999u128 * 88888 * 77777 * 66666 * 55555;
+
+        let err = total::<usize>(&data).unwrap_err();
+        assert!(err.is::<Overflow>());
+        assert_eq!(product + 15, total::<u128>(&data)?);
+        assert_eq!(BigUint::from(product + 15), total::<BigUint>(&data)?);
+
+        // a number that's too long for the type, not just an answer
+        let data = grid(&["123456789012345678901234567890", "1", "+"]);
+        assert!(total::<usize>(&data).unwrap_err().root_cause().is::<Overflow>());
+        assert_eq!(123456789012345678901234567891u128, total::<u128>(&data)?);
         Ok(())
     }
 
     #[test]
-    fn test_process_full() -> anyhow::Result<()> {
-        let input = Path::new("../inputs/day6.txt");
+    fn test_operator_errors() {
+        let err = solve_problems(&grid(&["1 7", "0 8", "/ -"])).unwrap_err();
+        assert_eq!("Division of 1 by zero", err.to_string());
 
-        assert_eq!(6605396225322, process(&input)?);
-        Ok(())
+        let err = solve_problems(&grid(&["7", "8", "-"])).unwrap_err();
+        assert_eq!("7 - 8 is below zero", err.to_string());
     }
 }
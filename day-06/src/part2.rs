@@ -1,88 +1,43 @@
 use std::path::Path;
-use std::fs::read_to_string;
 
-pub fn read_txt(path: &Path) -> Vec<Vec<char>> {
-    let file_text = read_to_string(path).expect("Failed to read file");
+use crate::grid::TextGrid;
+use crate::int::Number;
+use crate::operators::OperatorRegistry;
+use crate::worksheet::{self, ReadingMode};
 
-    let mut problems = vec![];
-
-    let mut lines = file_text.lines();
-
-    while let Some(line) = lines.next() {
-        problems.push(line.chars().collect::<Vec<char>>());
-    }
-
-    problems
+pub fn read_txt(path: &Path) -> anyhow::Result<TextGrid> {
+    TextGrid::read(path)
 }
 
-pub fn create_num_from_chars(chars: &[char]) -> Option<usize> {
-    if chars.iter().all(|c| c == &' ') {
-        return None
-    }
-    // iterate backwards over the char
-    let mut multiple = 1;
-    let mut value = 0;
-    let n_chars = chars.len();
-
-    for i in 0..n_chars {
-        if chars[n_chars - i - 1] == ' ' { continue }
-        value += (chars[n_chars - i - 1] as u32 - '0' as u32) * multiple;
-        multiple *= 10;
-    }
+pub fn solve_problems(grid: &TextGrid) -> anyhow::Result<Vec<usize>> {
+    solve_problems_with(grid, &OperatorRegistry::default())
+}
 
-    Some(value as usize)
+pub fn solve_problems_with<T: Number>(grid: &TextGrid, registry: &OperatorRegistry<T>) -> anyhow::Result<Vec<T>> {
+    worksheet::solve_problems(grid, ReadingMode::ColumnsRightToLeft, registry)
 }
 
-pub fn solve_problems(data: &Vec<Vec<char>>) -> Vec<usize> {
-    let mut problems = vec![];
-    let n_vals = data.len() - 1;
-    let n_cols = data[0].len();
-    let mut operator = ' ';
-
-    let mut numbers = vec![];
-    for j in (0..n_cols).rev() {
-        // to skip any column separators
-        if operator != ' ' {
-            operator = data[n_vals][j];
-            continue 
-        }
-
-        // we always compute the number from the row
-        let num = create_num_from_chars(&(0..n_vals).map(|v| data[v][j]).collect::<Vec<char>>());
-        numbers.push(num);
-
-        if data[n_vals][j] != ' ' {
-            operator = data[n_vals][j];
-            let value = match operator {
-                '*' => numbers.iter().flatten().product(),
-                '+' => numbers.iter().flatten().sum(),
-                _ => unreachable!()
-            };
-            problems.push(value);
-            numbers.clear();
-        }
-    }
-    problems
+pub fn total<T: Number>(grid: &TextGrid) -> anyhow::Result<T> {
+    worksheet::total(grid, ReadingMode::ColumnsRightToLeft)
 }
 
 #[tracing::instrument]
 pub fn process(input: &Path) -> anyhow::Result<usize> {
-    let data = read_txt(input);
-    let solutions = solve_problems(&data);
-
-    Ok(solutions.iter().sum())
+    total(&read_txt(input)?)
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::int::Overflow;
+    use num_bigint::BigUint;
     use rstest::rstest;
 
     #[test]
     fn test_process() -> anyhow::Result<()> {
         let input = Path::new("../inputs/day6_sample.txt");
 
-        assert_eq!(3263827, process(&input)?);
+        assert_eq!(3263827, process(input)?);
         Ok(())
     }
 
@@ -90,7 +45,7 @@ mod tests {
     fn test_process_full() -> anyhow::Result<()> {
         let input = Path::new("../inputs/day6.txt");
 
-        assert_eq!(11052310600986, process(&input)?);
+        assert_eq!(11052310600986, process(input)?);
         Ok(())
     }
 
@@ -98,34 +53,35 @@ mod tests {
     fn test_read_txt_small() {
         let input = Path::new("../inputs/day6_sample.txt");
 
-        let res = read_txt(input);
+        let res = read_txt(input).unwrap();
 
         let line1 = vec!['1', '2', '3', ' ', '3', '2', '8', ' ', ' ', '5', '1', ' ', '6', '4', ' '];
-        assert_eq!(res[0], line1);
+        assert_eq!(res.row(0), line1);
 
         let line2 = vec![' ', '4', '5', ' ', '6', '4', ' ', ' ', '3', '8', '7', ' ', '2', '3', ' '];
-        assert_eq!(res[1], line2);
+        assert_eq!(res.row(1), line2);
 
         let line3 = vec![' ', ' ', '6', ' ', '9', '8', ' ', ' ', '2', '1', '5', ' ', '3', '1', '4'];
-        assert_eq!(res[2], line3);
+        assert_eq!(res.row(2), line3);
 
         let line4 = vec!['*', ' ', ' ', ' ', '+', ' ', ' ', ' ', '*', ' ', ' ', ' ', '+', ' ', ' '];
-        assert_eq!(res[3], line4);
+        assert_eq!(res.row(3), line4);
     }
 
-    #[rstest]
-    #[case::all_nums(vec!['1', '2', '3'], Some(123))]
-    #[case::all_nums(vec![' ', ' ', '3'], Some(3))]
-    #[case::all_nums(vec!['1', ' ', ' '], Some(1))]
-    #[case::all_nums(vec!['1', '2', ' '], Some(12))]
-    #[case::all_nums(vec![' ', '2', '3'], Some(23))]
-    #[case::all_nums(vec![' ', ' ', ' '], None)]
-    fn test_create_num_from_chars(#[case] vals: Vec<char>, #[case] answer: Option<usize>) {
-        assert_eq!(create_num_from_chars(&vals), answer)
+    #[test]
+    fn test_total_overflow() -> anyhow::Result<()> {
+        // one problem of five 5 digit numbers written down the columns
+        let data = grid(&["98765", "98765", "98765", "98765", "98765", "*    "]);
+        let product = 99999u128 * 88888 * 77777 * 66666 * 55555;
+
+        assert!(total::<usize>(&data).unwrap_err().is::<Overflow>());
+        assert_eq!(product, total::<u128>(&data)?);
+        assert_eq!(BigUint::from(product), total::<BigUint>(&data)?);
+        Ok(())
     }
 
     #[test]
-    fn test_solve_problems_new() {
+    fn test_solve_problems_new() -> anyhow::Result<()> {
         let data = vec![
             vec!['1', '2', '3', ' ', '3', '2', '8', ' ', ' ', '5', '1', ' ', '6', '4', ' '],
             vec![' ', '4', '5', ' ', '6', '4', ' ', ' ', '3', '8', '7', ' ', '2', '3', ' '],
@@ -133,15 +89,66 @@ mod tests {
             vec!['*', ' ', ' ', ' ', '+', ' ', ' ', ' ', '*', ' ', ' ', ' ', '+', ' ', ' '],
         ];
 
-        let res = solve_problems(&data);
+        let res = solve_problems(&TextGrid::from(data))?;
 
         let answer = vec![
-            vec![623, 431, 4].iter().sum(),
-            vec![32, 581, 175].iter().product(),
-            vec![369, 248, 8].iter().sum(),
-            vec![1, 24, 356].iter().product(),
+            [623, 431, 4].iter().sum(),
+            [32, 581, 175].iter().product(),
+            [369, 248, 8].iter().sum(),
+            [1, 24, 356].iter().product(),
         ];
 
         assert_eq!(res, answer);
+        Ok(())
+    }
+
+    #[test]
+    fn test_unknown_operator() {
+        let data = vec![
+            vec!['1', ' ', '2'],
+            vec!['3', ' ', '4'],
+            vec!['%', ' ', '+'],
+        ];
+
+        let err = solve_problems(&TextGrid::from(data)).unwrap_err();
+        assert_eq!("Unknown operator \"%\", expected one of * + - / ^ avg gcd lcm max min", err.to_string());
+    }
+
+    fn grid(lines: &[&str]) -> TextGrid {
+        TextGrid::parse(&lines.join("\n"))
+    }
+
+    #[rstest]
+    // 648 - 1
+    #[case::subtract(&["  6", " 14", "  8", "-  "], 647)]
+    // 216 / 9
+    #[case::divide(&[" 2 ", " 1 ", "96 ", "/  "], 24)]
+    // 3 ^ 12
+    #[case::power(&["1 ", "23", "^ "], 531441)]
+    // 68 and 34 under a token wider than the problem
+    #[case::min(&["36", "48", "min"], 34)]
+    #[case::max(&["36", "48", "max"], 68)]
+    #[case::gcd(&["36", "48", "gcd"], 34)]
+    #[case::lcm(&["36", "48", "lcm"], 68)]
+    #[case::avg(&["36", "48", "avg"], 51)]
+    fn test_operators(#[case] lines: &[&str], #[case] answer: usize) -> anyhow::Result<()> {
+        assert_eq!(vec![answer], solve_problems(&grid(lines))?);
+        Ok(())
+    }
+
+    #[test]
+    fn test_ragged_rows() -> anyhow::Result<()> {
+        // a long token over a narrow problem, and rows that stop short of the last column
+        let data = grid(&["12  7", "3   4", "max +"]);
+
+        assert_eq!(vec![74, 13], solve_problems(&data)?);
+        // the + in column 3 has no numbers over it
+        let err = solve_problems(&grid(&["5", "+ +"])).unwrap_err();
+        assert_eq!("There are no numbers over \"+\" in column 3", err.to_string());
+
+        // the sample with the trailing spaces trimmed off
+        let data = grid(&["123 328  51 64", " 45 64  387 23", "  6 98  215 314", "*   +   *   +"]);
+        assert_eq!(3263827, total::<usize>(&data)?);
+        Ok(())
     }
 }
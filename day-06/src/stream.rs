@@ -0,0 +1,288 @@
+use std::fs::File;
+use std::io::{BufRead, BufReader, Seek, SeekFrom};
+use std::path::Path;
+
+use anyhow::{bail, Context};
+
+use crate::grid::{Block, TextGrid};
+use crate::int::{overflow, Number};
+use crate::operators::OperatorRegistry;
+use crate::worksheet::{work_problems, ReadingMode, Worked};
+
+/// Reads a sheet a column at a time with a reader per row, so a sheet with very long lines
+/// never has to be in memory all at once
+pub struct ColumnReader<R: BufRead> {
+    rows: Vec<R>,
+    done: Vec<bool>,
+}
+
+/// The most rows `ColumnReader::open` takes, each one holds a file handle open
+pub const MAX_ROWS: usize = 256;
+
+impl ColumnReader<BufReader<File>> {
+    /// Opens the file once per row of the sheet, each seeked to the start of its line. Sheets
+    /// taller than `MAX_ROWS` are an error rather than running out of file handles.
+    pub fn open(path: &Path) -> anyhow::Result<Self> {
+        let file = File::open(path).with_context(|| format!("Failed to read {}", path.display()))?;
+        let starts = line_starts(BufReader::new(file)).with_context(|| format!("Failed to read {}", path.display()))?;
+        if starts.len() > MAX_ROWS {
+            bail!("{} has {} rows, only sheets of up to {MAX_ROWS} rows can be streamed", path.display(), starts.len())
+        }
+
+        let rows = starts.iter().map(|start| {
+            let mut file = File::open(path)?;
+            file.seek(SeekFrom::Start(*start))?;
+            Ok(BufReader::new(file))
+        }).collect::<std::io::Result<Vec<_>>>().with_context(|| format!("Failed to read {}", path.display()))?;
+
+        Ok(ColumnReader::from_rows(rows))
+    }
+}
+
+impl<R: BufRead> ColumnReader<R> {
+    /// A reader per row, each reading from the start of its line
+    pub fn from_rows(rows: Vec<R>) -> Self {
+        let done = vec![false; rows.len()];
+        ColumnReader { rows, done }
+    }
+
+    pub fn height(&self) -> usize {
+        self.rows.len()
+    }
+
+    /// The next column top to bottom as bytes, rows that have ended give spaces. None once
+    /// every row has ended.
+    pub fn next_column(&mut self) -> anyhow::Result<Option<Vec<u8>>> {
+        let mut column = Vec::with_capacity(self.rows.len());
+
+        for (row, done) in self.rows.iter_mut().zip(self.done.iter_mut()) {
+            let byte = match *done {
+                true => None,
+                false => row.fill_buf()?.first().copied(),
+            };
+            match byte {
+                None | Some(b'\n') => {
+                    *done = true;
+                    column.push(b' ');
+                }
+                Some(byte) => {
+                    row.consume(1);
+                    if !byte.is_ascii() {
+                        bail!("Only ASCII sheets can be streamed, found byte {byte:#x} in row {}", column.len() + 1)
+                    }
+                    // the \r of a \r\n line ending is a blank column at the end of the row
+                    column.push(if byte == b'\r' { b' ' } else { byte });
+                }
+            }
+        }
+
+        Ok((!self.done.iter().all(|d| *d)).then_some(column))
+    }
+}
+
+// where each line starts, without the blank lines at the end
+fn line_starts(mut reader: impl BufRead) -> std::io::Result<Vec<u64>> {
+    let mut lines: Vec<(u64, bool)> = vec![];
+    let (mut pos, mut in_line) = (0u64, false);
+
+    loop {
+        let buf = reader.fill_buf()?;
+        if buf.is_empty() {
+            break
+        }
+        for byte in buf {
+            if !in_line {
+                lines.push((pos, true));
+                in_line = true;
+            }
+            if *byte == b'\n' {
+                in_line = false;
+            } else if !byte.is_ascii_whitespace() {
+                lines.last_mut().expect("a line was just started").1 = false;
+            }
+            pos += 1;
+        }
+        let len = buf.len();
+        reader.consume(len);
+    }
+
+    while lines.last().is_some_and(|(_, blank)| *blank) {
+        lines.pop();
+    }
+    Ok(lines.into_iter().map(|(start, _)| start).collect())
+}
+
+/// Calls `f` with each problem of the sheet as soon as its last column has been read, left
+/// to right. Each problem comes as a grid of just its own columns, with where it is on the sheet.
+pub fn for_each_block<R: BufRead>(reader: &mut ColumnReader<R>, mut f: impl FnMut(Block, &TextGrid) -> anyhow::Result<()>) -> anyhow::Result<()> {
+    let mut columns: Vec<Vec<u8>> = vec![];
+    let mut j = 0;
+
+    loop {
+        let column = reader.next_column()?;
+        let blank = column.as_ref().is_none_or(|c| c.iter().all(|b| *b == b' '));
+
+        if blank && !columns.is_empty() {
+            let rows = (0..reader.height()).map(|i| columns.iter().map(|c| c[i] as char).collect()).collect::<Vec<Vec<char>>>();
+            f(Block { start: j - columns.len(), end: j }, &TextGrid::from(rows))?;
+            columns.clear();
+        }
+        match column {
+            None => return Ok(()),
+            Some(column) if !blank => columns.push(column),
+            Some(_) => {}
+        }
+        j += 1;
+    }
+}
+
+/// Solves the sheet a problem at a time like `work_problems`, except that the problems
+/// always come left to right
+pub fn stream_problems<T: Number, R: BufRead>(
+    reader: &mut ColumnReader<R>,
+    mode: ReadingMode,
+    registry: &OperatorRegistry<T>,
+    mut emit: impl FnMut(Worked<T>) -> anyhow::Result<()>,
+) -> anyhow::Result<()> {
+    if reader.height() == 0 {
+        bail!("The worksheet is empty")
+    }
+    for_each_block(reader, |block, grid| {
+        for worked in work_problems(grid, mode, registry).with_context(|| format!("Failed to solve {block}"))? {
+            emit(Worked { block, ..worked })?;
+        }
+        Ok(())
+    })
+}
+
+/// The sum of every problem's answer, reading the file a column at a time
+pub fn stream_total<T: Number>(path: &Path, mode: ReadingMode) -> anyhow::Result<T> {
+    let mut total = T::zero();
+    stream_problems(&mut ColumnReader::open(path)?, mode, &OperatorRegistry::default(), |worked| {
+        total = total.checked_add(&worked.answer).ok_or_else(|| overflow::<T>("the grand total"))?;
+        Ok(())
+    })?;
+    Ok(total)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::generate::generate_worksheet;
+    use crate::worksheet::total;
+    use num_bigint::BigUint;
+    use proptest::prelude::*;
+
+    const SAMPLE: &str = "123 328  51 64 \n 45 64  387 23 \n  6 98  215 314\n*   +   *   +  \n";
+
+    // a reader per line like `open` makes, blank lines at the end left out the same way
+    fn reader(text: &str) -> ColumnReader<&[u8]> {
+        let starts = line_starts(text.as_bytes()).unwrap();
+        ColumnReader::from_rows(starts.iter().map(|start| &text.as_bytes()[*start as usize..]).collect())
+    }
+
+    fn stream(text: &str, mode: ReadingMode) -> anyhow::Result<Vec<Worked>> {
+        let mut problems = vec![];
+        stream_problems(&mut reader(text), mode, &OperatorRegistry::default(), |worked| {
+            problems.push(worked);
+            Ok(())
+        })?;
+        Ok(problems)
+    }
+
+    #[test]
+    fn test_line_starts() -> anyhow::Result<()> {
+        assert_eq!(vec![0, 3, 4, 7], line_starts(&b"12\n\n3\r\n456\n \n\n"[..])?);
+        assert_eq!(vec![0], line_starts(&b"12"[..])?);
+        assert!(line_starts(&b""[..])?.is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn test_next_column() -> anyhow::Result<()> {
+        let mut columns = reader("12\n3\r\n456");
+
+        assert_eq!(Some(b"134".to_vec()), columns.next_column()?);
+        assert_eq!(Some(b"2 5".to_vec()), columns.next_column()?);
+        assert_eq!(Some(b"  6".to_vec()), columns.next_column()?);
+        assert_eq!(None, columns.next_column()?);
+        Ok(())
+    }
+
+    #[test]
+    fn test_matches_grid() -> anyhow::Result<()> {
+        // a right to left mode still gives the problems left to right
+        let worked = stream(SAMPLE, ReadingMode::ColumnsRightToLeft)?;
+        assert_eq!("columns 1-3: 356 * 24 * 1 = 8544", worked[0].to_string());
+        assert_eq!("columns 13-15: 4 + 431 + 623 = 1058", worked[3].to_string());
+
+        let ragged = "123 328  51 64\r\n 45 64  387 23\r\n  6 98  215 314\r\n*   +   *   +\r\n\r\n";
+        for mode in ReadingMode::ALL {
+            let expected = total::<usize>(&TextGrid::parse(SAMPLE), mode)?;
+            assert_eq!(expected, stream(SAMPLE, mode)?.iter().map(|w| w.answer).sum::<usize>(), "{mode}");
+            assert_eq!(expected, stream(ragged, mode)?.iter().map(|w| w.answer).sum::<usize>(), "{mode}");
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_errors() {
+        let err = stream("1 2\n+ %\n", ReadingMode::Rows).unwrap_err();
+        assert_eq!("Failed to solve column 3", err.to_string());
+        assert!(stream("1 é\n+ +\n", ReadingMode::Rows).is_err());
+        assert_eq!("The worksheet is empty", stream("\n\n", ReadingMode::Rows).unwrap_err().to_string());
+    }
+
+    #[test]
+    fn test_stream_file() -> anyhow::Result<()> {
+        let path = std::env::temp_dir().join(format!("day-06-stream-{}.txt", std::process::id()));
+        // a wide sheet, the same four problems over and over
+        let rows = SAMPLE.lines().map(|line| format!("{line} ").repeat(500)).collect::<Vec<String>>();
+        std::fs::write(&path, rows.join("\n") + "\n\n")?;
+
+        let streamed = stream_total::<usize>(&path, ReadingMode::ColumnsRightToLeft);
+        let read = total::<usize>(&TextGrid::read(&path)?, ReadingMode::ColumnsRightToLeft);
+        std::fs::remove_file(&path)?;
+
+        assert_eq!(3263827 * 500, streamed?);
+        assert_eq!(3263827 * 500, read?);
+        Ok(())
+    }
+
+    #[test]
+    fn test_too_many_rows() -> anyhow::Result<()> {
+        let path = std::env::temp_dir().join(format!("day-06-tall-{}.txt", std::process::id()));
+        std::fs::write(&path, "1\n".repeat(MAX_ROWS) + "+\n")?;
+
+        let err = ColumnReader::open(&path).err();
+        std::fs::remove_file(&path)?;
+
+        let expected = format!("{} has 257 rows, only sheets of up to 256 rows can be streamed", path.display());
+        assert_eq!(Some(expected), err.map(|e| e.to_string()));
+        Ok(())
+    }
+
+    proptest! {
+        #[test]
+        fn prop_matches_grid(problems in 0usize..30, rows in 1usize..6, max_digits in 1usize..6, seed in any::<u64>(), crlf in any::<bool>()) {
+            let operators = ["+", "*", "max"].map(String::from);
+            let mut sheet = vec![];
+            generate_worksheet(&mut sheet, problems, rows, max_digits, &operators, seed).unwrap();
+            let mut sheet = String::from_utf8(sheet).unwrap();
+            if crlf {
+                sheet = sheet.replace('\n', "\r\n");
+            }
+
+            for mode in ReadingMode::ALL {
+                let mut streamed = BigUint::default();
+                let done = stream_problems(&mut reader(&sheet), mode, &OperatorRegistry::<BigUint>::default(), |worked| {
+                    streamed += worked.answer;
+                    Ok(())
+                });
+                // an empty sheet is an error either way
+                let read = total::<BigUint>(&TextGrid::parse(&sheet), mode).map_err(|err| err.to_string());
+                prop_assert_eq!(read, done.map(|_| streamed).map_err(|err| err.to_string()));
+            }
+        }
+    }
+}
@@ -0,0 +1,273 @@
+use std::fmt::Display;
+use std::str::FromStr;
+
+use anyhow::{bail, Context};
+
+use crate::grid::{Block, TextGrid};
+use crate::int::{checked_sum, parse_number, Number};
+use crate::operators::{Operator, OperatorRegistry};
+
+/// One problem of the worksheet, the numbers of a column and what to do with them
+pub trait Problem<T: Number = usize> {
+    fn solve(&self) -> anyhow::Result<T>;
+}
+
+pub struct OperatorProblem<'a, T: Number = usize> {
+    pub vals: Vec<T>,
+    pub operator: &'a dyn Operator<T>,
+}
+
+impl<T: Number> Problem<T> for OperatorProblem<'_, T> {
+    fn solve(&self) -> anyhow::Result<T> {
+        self.operator.apply(&self.vals)
+    }
+}
+
+/// The number written down a column, None for a blank column
+pub fn create_num_from_chars<T: Number>(chars: &[char]) -> anyhow::Result<Option<T>> {
+    let digits = chars.iter().filter(|c| **c != ' ').collect::<String>();
+    if digits.is_empty() {
+        return Ok(None)
+    }
+    parse_number(&digits).map(Some)
+}
+
+/// How the numbers of a problem are read off the sheet
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReadingMode {
+    /// A number per row, problems left to right, how part 1 reads the sheet
+    Rows,
+    /// A number per column with its digits top to bottom, problems and columns right to
+    /// left, how part 2 reads the sheet
+    ColumnsRightToLeft,
+    /// Like `ColumnsRightToLeft` with problems and columns left to right
+    ColumnsLeftToRight,
+    /// Like `ColumnsRightToLeft` with the digits of each column bottom to top
+    ColumnsBottomToTop,
+}
+
+impl ReadingMode {
+    pub const ALL: [ReadingMode; 4] = [ReadingMode::Rows, ReadingMode::ColumnsRightToLeft, ReadingMode::ColumnsLeftToRight, ReadingMode::ColumnsBottomToTop];
+
+    /// The mode a puzzle part reads the sheet in
+    pub fn for_part(part: usize) -> anyhow::Result<Self> {
+        match part {
+            1 => Ok(ReadingMode::Rows),
+            2 => Ok(ReadingMode::ColumnsRightToLeft),
+            _ => bail!("Unknown part {part}"),
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            ReadingMode::Rows => "rows",
+            ReadingMode::ColumnsRightToLeft => "columns-rtl",
+            ReadingMode::ColumnsLeftToRight => "columns-ltr",
+            ReadingMode::ColumnsBottomToTop => "columns-up",
+        }
+    }
+
+    fn right_to_left(&self) -> bool {
+        matches!(self, ReadingMode::ColumnsRightToLeft | ReadingMode::ColumnsBottomToTop)
+    }
+
+    /// The problems of the sheet in the order this mode reads them
+    pub fn blocks(&self, grid: &TextGrid) -> Vec<Block> {
+        let mut blocks = grid.blocks();
+        if self.right_to_left() {
+            blocks.reverse();
+        }
+        blocks
+    }
+
+    /// The numbers of the problem in `block`, in reading order. Only the first `rows` rows
+    /// hold numbers and blank rows or columns are skipped.
+    pub fn read_numbers<T: Number>(&self, grid: &TextGrid, block: &Block, rows: usize) -> anyhow::Result<Vec<T>> {
+        if *self == ReadingMode::Rows {
+            return (0..rows)
+                .map(|i| (i, grid.cell(i, block)))
+                .filter(|(_, cell)| !cell.is_empty())
+                .map(|(i, cell)| parse_number(&cell).with_context(|| format!("Invalid number in row {}", i + 1)))
+                .collect()
+        }
+
+        let mut columns = block.columns().collect::<Vec<usize>>();
+        if self.right_to_left() {
+            columns.reverse();
+        }
+        columns.into_iter()
+            .map(|j| {
+                let mut digits = grid.column(j).take(rows).collect::<Vec<char>>();
+                if *self == ReadingMode::ColumnsBottomToTop {
+                    digits.reverse();
+                }
+                create_num_from_chars(&digits).with_context(|| format!("Invalid number in column {}", j + 1))
+            })
+            .filter_map(|num| num.transpose())
+            .collect()
+    }
+}
+
+impl Display for ReadingMode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+impl FromStr for ReadingMode {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match ReadingMode::ALL.into_iter().find(|mode| mode.name() == s) {
+            Some(mode) => Ok(mode),
+            None => bail!("Unknown reading mode {s:?}, expected one of {}", ReadingMode::ALL.map(|m| m.name()).join(" ")),
+        }
+    }
+}
+
+/// A problem as it was read off the sheet, for `--explain`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Worked<T: Number = usize> {
+    pub block: Block,
+    /// In the order they were read
+    pub vals: Vec<T>,
+    pub symbol: String,
+    pub answer: T,
+}
+
+impl<T: Number> Display for Worked<T> {
+    /// `columns 13-15: 4 + 431 + 623 = 1058`, or `min(68, 34) = 34` for word operators
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}: ", self.block)?;
+        let vals = self.vals.iter().map(|v| v.to_string()).collect::<Vec<String>>();
+        if self.symbol.chars().all(|c| c.is_alphanumeric()) {
+            write!(f, "{}({})", self.symbol, vals.join(", "))?;
+        } else {
+            write!(f, "{}", vals.join(&format!(" {} ", self.symbol)))?;
+        }
+        write!(f, " = {}", self.answer)
+    }
+}
+
+/// Solves every problem in reading order, keeping what was read for each. The operator is
+/// always on the last row.
+pub fn work_problems<T: Number>(grid: &TextGrid, mode: ReadingMode, registry: &OperatorRegistry<T>) -> anyhow::Result<Vec<Worked<T>>> {
+    let mut problems = vec![];
+    let ops = grid.height().checked_sub(1).context("The worksheet is empty")?;
+
+    for block in mode.blocks(grid) {
+        let vals = mode.read_numbers(grid, &block, ops)?;
+        let symbol = grid.cell(ops, &block);
+        if vals.is_empty() {
+            bail!("There are no numbers over {symbol:?} in {block}")
+        }
+        let problem = OperatorProblem { vals, operator: registry.lookup(&symbol)? };
+        let answer = problem.solve()?;
+        problems.push(Worked { block, vals: problem.vals, symbol, answer });
+    }
+    Ok(problems)
+}
+
+pub fn solve_problems<T: Number>(grid: &TextGrid, mode: ReadingMode, registry: &OperatorRegistry<T>) -> anyhow::Result<Vec<T>> {
+    Ok(work_problems(grid, mode, registry)?.into_iter().map(|worked| worked.answer).collect())
+}
+
+/// The sum of every problem's answer, worked out in `T`
+pub fn total<T: Number>(grid: &TextGrid, mode: ReadingMode) -> anyhow::Result<T> {
+    checked_sum(&solve_problems(grid, mode, &OperatorRegistry::default())?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::int::Overflow;
+    use rstest::rstest;
+
+    fn sample() -> TextGrid {
+        TextGrid::parse("123 328  51 64 \n 45 64  387 23 \n  6 98  215 314\n*   +   *   +  ")
+    }
+
+    fn solve(grid: &TextGrid, mode: ReadingMode) -> anyhow::Result<Vec<usize>> {
+        solve_problems(grid, mode, &OperatorRegistry::default())
+    }
+
+    #[test]
+    fn test_modes() -> anyhow::Result<()> {
+        let grid = sample();
+
+        assert_eq!(vec![33210, 490, 4243455, 401], solve(&grid, ReadingMode::Rows)?);
+        assert_eq!(vec![1058, 3253600, 625, 8544], solve(&grid, ReadingMode::ColumnsRightToLeft)?);
+        assert_eq!(vec![8544, 625, 3253600, 1058], solve(&grid, ReadingMode::ColumnsLeftToRight)?);
+        assert_eq!(vec![4 + 134 + 326, 571 * 185 * 23, 8 + 842 + 963, 653 * 42], solve(&grid, ReadingMode::ColumnsBottomToTop)?);
+
+        assert_eq!(4277556usize, total(&grid, ReadingMode::for_part(1)?)?);
+        assert_eq!(3263827usize, total(&grid, ReadingMode::for_part(2)?)?);
+        Ok(())
+    }
+
+    #[test]
+    fn test_rows_skip_blank_cells() -> anyhow::Result<()> {
+        let grid = TextGrid::parse("12  7\n    8\n3    \n*   +");
+
+        assert_eq!(vec![36, 15], solve(&grid, ReadingMode::Rows)?);
+        let err = solve(&TextGrid::parse("1 2\n34 \n+  "), ReadingMode::Rows).unwrap_err();
+        assert_eq!("Invalid number in row 1", err.to_string());
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_mode() -> anyhow::Result<()> {
+        for mode in ReadingMode::ALL {
+            assert_eq!(mode, mode.to_string().parse()?);
+        }
+        assert_eq!(
+            "Unknown reading mode \"diagonal\", expected one of rows columns-rtl columns-ltr columns-up",
+            "diagonal".parse::<ReadingMode>().unwrap_err().to_string()
+        );
+        assert!(ReadingMode::for_part(3).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_explain() -> anyhow::Result<()> {
+        let worked = work_problems::<usize>(&sample(), ReadingMode::ColumnsRightToLeft, &OperatorRegistry::default())?;
+        let lines = worked.iter().map(|w| w.to_string()).collect::<Vec<String>>();
+
+        assert_eq!(vec![
+            "columns 13-15: 4 + 431 + 623 = 1058",
+            "columns 9-11: 175 * 581 * 32 = 3253600",
+            "columns 5-7: 8 + 248 + 369 = 625",
+            "columns 1-3: 356 * 24 * 1 = 8544",
+        ], lines);
+
+        let worked = work_problems::<usize>(&sample(), ReadingMode::Rows, &OperatorRegistry::default())?;
+        assert_eq!("columns 1-3: 123 * 45 * 6 = 33210", worked[0].to_string());
+
+        let grid = TextGrid::parse("36  1\n48  2\nmin +");
+        let worked = work_problems::<usize>(&grid, ReadingMode::ColumnsRightToLeft, &OperatorRegistry::default())?;
+        assert_eq!("column 5: 12 = 12", worked[0].to_string());
+        assert_eq!("columns 1-3: min(68, 34) = 34", worked[1].to_string());
+        assert_eq!(Block { start: 0, end: 3 }, worked[1].block);
+        Ok(())
+    }
+
+    #[rstest]
+    #[case::all_nums(vec!['1', '2', '3'], Some(123))]
+    #[case::all_nums(vec![' ', ' ', '3'], Some(3))]
+    #[case::all_nums(vec!['1', ' ', ' '], Some(1))]
+    #[case::all_nums(vec!['1', '2', ' '], Some(12))]
+    #[case::all_nums(vec![' ', '2', '3'], Some(23))]
+    #[case::all_nums(vec![' ', ' ', ' '], None)]
+    fn test_create_num_from_chars(#[case] vals: Vec<char>, #[case] answer: Option<usize>) -> anyhow::Result<()> {
+        assert_eq!(create_num_from_chars(&vals)?, answer);
+        Ok(())
+    }
+
+    #[test]
+    fn test_create_num_errors() {
+        assert!(create_num_from_chars::<usize>(&['1', 'x']).is_err());
+        let tall = ['9'; 25];
+        assert!(create_num_from_chars::<usize>(&tall).unwrap_err().is::<Overflow>());
+        assert_eq!(Some("9".repeat(25).parse::<u128>().unwrap()), create_num_from_chars(&tall).unwrap());
+    }
+}